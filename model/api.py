@@ -16,6 +16,9 @@ libcc0.free_json_buffer.restype = None
 libcc0.set_random_seed.argtypes = [ctypes.c_uint32]
 libcc0.set_random_seed.restype = None
 
+libcc0.set_move_ordering.argtypes = [ctypes.c_bool]
+libcc0.set_move_ordering.restype = None
+
 libcc0.new_small_game.argtypes = []
 libcc0.new_small_game.restype = ctypes.c_void_p
 
@@ -67,6 +70,9 @@ libcc0.game_load_key.restype = None
 libcc0.alphabeta.argtypes = [ctypes.c_void_p, ctypes.c_size_t]
 libcc0.alphabeta.restype = None
 
+libcc0.alphabeta_report.argtypes = [ctypes.c_void_p, ctypes.c_size_t]
+libcc0.alphabeta_report.restype = None
+
 libcc0.greedy.argtypes = [ctypes.c_void_p, ctypes.c_double]
 libcc0.greedy.restype = None
 
@@ -147,13 +153,25 @@ class Game:
     def __del__(self):
         libcc0.free_game(self.ptr)
 
+# keys are [version, board fingerprint (4 bytes), turn (4 bytes), pieces...], see Game::key
+def key_turn(key):
+    return int.from_bytes(bytes(key[5:9]), "little")
+
 def set_random_seed(seed):
     libcc0.set_random_seed(seed)
 
+def set_move_ordering(enabled):
+    libcc0.set_move_ordering(enabled)
+
 def alphabeta(game, depth):
     libcc0.alphabeta(game.ptr, depth)
     return read_wasm_json()
 
+# {"path", "score", "pv", "nodes", "depth"}, where pv is the expected line of play as paths
+def alphabeta_report(game, depth):
+    libcc0.alphabeta_report(game.ptr, depth)
+    return read_wasm_json()
+
 def greedy(game, temperature):
     libcc0.greedy(game.ptr, temperature)
     return read_wasm_json()
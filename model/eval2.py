@@ -24,16 +24,16 @@ def run_action(game, model):
     match model:
         case "greedy":
             action = greedy(game, 0.1)
-            game.move_to(*action)
+            game.move_to(action[0], action[-1])
         case "alphabeta2":
             action = alphabeta(game, 2)
-            game.move_to(*action)
+            game.move_to(action[0], action[-1])
         case "alphabeta3":
             action = alphabeta(game, 3)
-            game.move_to(*action)
+            game.move_to(action[0], action[-1])
         case "alphabeta4":
             action = alphabeta(game, 4)
-            game.move_to(*action)
+            game.move_to(action[0], action[-1])
         case _:
             original_key = game.key()
             child_keys = game.expand()
@@ -1,6 +1,9 @@
 import torch
 import numpy as np
 
+KEY_HEADER_SIZE = 9 # version, board fingerprint and turn, see Game::key
+KEY_TURN_BYTE = 5 # the lowest byte of the turn
+
 class Transformer(torch.nn.Module):
     def __init__(self, board_size):
         super(Transformer, self).__init__()
@@ -36,12 +39,12 @@ class Transformer(torch.nn.Module):
             for piece in game.p2_pieces():
                 x.append(piece + 2 + game.board_size)
         else:
-            x = [0] if key[0] % 2 == 0 else [1]
+            x = [0] if key[KEY_TURN_BYTE] % 2 == 0 else [1]
 
-            for piece in key[1:game.board_size+1]:
+            for piece in key[KEY_HEADER_SIZE:KEY_HEADER_SIZE+game.board_size]:
                 x.append(piece + 2)
 
-            for piece in key[game.board_size+1:]:
+            for piece in key[KEY_HEADER_SIZE+game.board_size:]:
                 x.append(piece + 2 + game.board_size)
 
         return x
@@ -77,13 +80,13 @@ class MLP(torch.nn.Module):
             for piece in game.p2_pieces():
                 x[1 + game.board_size + piece] = 1
         else:
-            if key[0] % 2 == 0:
+            if key[KEY_TURN_BYTE] % 2 == 0:
                 x[0] = 1
 
-            for piece in key[1:game.board_size+1]:
+            for piece in key[KEY_HEADER_SIZE:KEY_HEADER_SIZE+game.board_size]:
                 x[1 + piece] = 1
 
-            for piece in key[game.board_size+1:]:
+            for piece in key[KEY_HEADER_SIZE+game.board_size:]:
                 x[1 + game.board_size + piece] = 1
 
         return x
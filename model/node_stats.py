@@ -0,0 +1,28 @@
+# compares the nodes searched by alphabeta with and without move ordering, on positions reached by greedy play
+from api import Game, set_random_seed, set_move_ordering, greedy, alphabeta_report
+
+import sys
+
+board_type = sys.argv[1] if len(sys.argv) > 1 else "standard"
+set_random_seed(7)
+
+positions = []
+game = Game(board_type)
+for i in range(40):
+    action = greedy(game, 0.3)
+    game.move_to(action[0], action[-1])
+    if i % 8 == 3:
+        positions.append(game.key())
+
+for depth in [3, 4]:
+    total = { False: 0, True: 0 }
+    for key in positions:
+        game.load_key(key)
+        for ordering in [False, True]:
+            set_move_ordering(ordering)
+            report = alphabeta_report(game, depth)
+            total[ordering] += report["nodes"]
+        print(f"depth {depth} turn {game.turn()}: score {report['score']}, expecting {[ (path[0], path[-1]) for path in report['pv'] ]}")
+    print(f"depth {depth}: {total[False]} nodes without move ordering, {total[True]} with ({100 * total[True] / total[False]:.1f}%)")
+
+set_move_ordering(True)
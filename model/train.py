@@ -1,7 +1,7 @@
 import torch
 import numpy as np
 from multiprocessing import Pool
-from api import Game, set_random_seed, greedy, alphabeta
+from api import Game, set_random_seed, greedy, alphabeta, key_turn
 from utils import save, load
 from model import Model
 
@@ -27,10 +27,10 @@ def gen_data(board_type, p1_type, p2_type):
                 match player_type:
                     case "greedy":
                         action = greedy(game, 0.1)
-                        game.move_to(*action)
+                        game.move_to(action[0], action[-1])
                     case "alphabeta":
                         action = alphabeta(game, 3)
-                        game.move_to(*action)
+                        game.move_to(action[0], action[-1])
                     case "model":
                         child_keys = game.expand()
                         batched_input = [ Model.encode_input(game, key) for key in child_keys ]
@@ -45,14 +45,14 @@ def gen_data(board_type, p1_type, p2_type):
                 p1win, ending_turn = _gen_data()
 
             case 1: # p1 win
-                p1win, ending_turn = 1, key[0]
+                p1win, ending_turn = 1, key_turn(key)
 
             case 2: # p2 win
-                p1win, ending_turn = 0, key[0]
+                p1win, ending_turn = 0, key_turn(key)
 
         if p1win != -1: # properly ended
             encoded_input = Model.encode_input(game, key)
-            weight = 0.8 ** (ending_turn - key[0])
+            weight = 0.8 ** (ending_turn - key_turn(key))
             data.append((encoded_input, p1win, weight))
 
         return p1win, ending_turn
@@ -1,108 +1,586 @@
-use std::collections::BTreeMap;
+use crate::{cache::ScoreCache, clock::now_millis, game::{Game, Action}, movegen::{MoveCursor, MoveOrder}, random_shuffle, lookup_score, tt::{Bound, Entry, TranspositionTable, bound_of}};
 
-use crate::{game::{Game, Action}, random_shuffle};
+// the limits of an iterative deepening search, which stops at whichever comes first. Depth 1 is always completed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Budget {
+    pub millis: Option<f64>, // wall-clock time since the search started
+    pub nodes: Option<usize>, // positions visited, counted over all depths
+    pub max_depth: usize,
+}
+
+// counts the nodes of a search and checks its budget
+struct Search {
+    budget: Budget,
+    start: f64,
+    nodes: usize,
+    enforced: bool, // false while the first depth is searched
+    ordering: Option<MoveOrdering>, // None to search the moves in generation order
+}
+
+impl Search {
+    fn new(budget: Budget, game: &Game, ordering: bool) -> Search {
+        let ordering = ordering.then(|| MoveOrdering::new(game.board.board_size));
+        Search { budget, start: now_millis(), nodes: 0, enforced: false, ordering }
+    }
+
+    fn unlimited(game: &Game, ordering: bool) -> Search {
+        Search::new(Budget { millis: None, nodes: None, max_depth: usize::MAX }, game, ordering)
+    }
+
+    // counts a node. Returns false if the search should stop.
+    fn visit(&mut self) -> bool {
+        self.nodes += 1;
+        if !self.enforced {
+            return true
+        }
+        self.budget.nodes.is_none_or(|nodes| self.nodes <= nodes) && !(self.nodes.is_multiple_of(256) && self.out_of_time()) // the clock is slow to read
+    }
 
-fn _alphabeta(game: &Game, remaining_depth: usize, alpha: f64, beta: f64) -> f64 {
-    if remaining_depth <= 0 {
-        return game.heuristic()
+    fn out_of_time(&self) -> bool {
+        self.budget.millis.is_some_and(|millis| now_millis() - self.start > millis)
     }
 
-    let (next_states, _) = game.expand(false);
-    if next_states.is_empty() {
-        return game.heuristic()
+    fn out_of_budget(&self) -> bool {
+        self.enforced && (self.budget.nodes.is_some_and(|nodes| self.nodes >= nodes) || self.out_of_time())
+    }
+
+    fn cutoff(&mut self, action: Action, remaining_depth: usize) {
+        if let Some(ordering) = &mut self.ordering {
+            ordering.cutoff(action, remaining_depth);
+        }
+    }
+}
+
+// what a search found. The score is from the view of player 1, like Game::heuristic or the win probability of the polling searches.
+#[derive(Clone, Debug)]
+pub struct SearchReport {
+    pub action: Action, // the move to play
+    pub score: f64,
+    pub pv: Vec<Action>, // the expected line of play, starting with action. It stops early where the line ends in a transposition table hit.
+    pub nodes: usize, // positions visited, counted over all depths
+    pub depth: usize, // the depth of the search that chose the move
+}
+
+// what earlier cutoffs tell about the moves of a search. The killers are the last two moves that caused a cutoff at each
+// remaining depth, which is the same ply within one depth of iterative deepening. The history adds up the cutoffs of each
+// move over the whole search, weighted by the square of the remaining depth.
+struct MoveOrdering {
+    killers: Vec<[Option<Action>; 2]>,
+    history: Vec<u32>, // indexed by from * board_size + to
+    board_size: usize,
+}
+
+impl MoveOrdering {
+    fn new(board_size: usize) -> MoveOrdering {
+        MoveOrdering { killers: vec![], history: vec![0; board_size * board_size], board_size }
+    }
+
+    fn cutoff(&mut self, action: Action, remaining_depth: usize) {
+        if self.killers.len() <= remaining_depth {
+            self.killers.resize(remaining_depth + 1, [None; 2]);
+        }
+        let killers = &mut self.killers[remaining_depth];
+        if killers[0] != Some(action) {
+            killers[1] = killers[0];
+            killers[0] = Some(action);
+        }
+
+        let Action(from, to) = action;
+        let history = &mut self.history[from as usize * self.board_size + to as usize];
+        *history = history.saturating_add((remaining_depth * remaining_depth) as u32);
+    }
+
+    // best first: the killers, then the moves that advance the most, then the moves with more cutoffs in the history
+    fn sort(&self, game: &Game, actions: &mut [Action], remaining_depth: usize) {
+        let killers = self.killers.get(remaining_depth).copied().unwrap_or_default();
+        let distance_map = &game.board.distance_maps[game.player_to_move()];
+        actions.sort_by_cached_key(|&action| {
+            let Action(from, to) = action;
+            let killer = killers.iter().position(|&killer| killer == Some(action)).unwrap_or(2);
+            let gain = distance_map[from as usize] as i64 - distance_map[to as usize] as i64;
+            (killer, std::cmp::Reverse(gain), std::cmp::Reverse(self.history[from as usize * self.board_size + to as usize]))
+        });
+    }
+}
+
+// the moves of a node in search order: the stored best move, then the others either as generated or sorted by the
+// move ordering of the search. The others are only generated if the stored best move does not cut off.
+struct MovePicker {
+    first: Option<Action>,
+    cursor: MoveCursor,
+    sorted: Option<std::vec::IntoIter<Action>>,
+    started: bool,
+}
+
+impl MovePicker {
+    fn new(game: &Game, first: Option<Action>) -> MovePicker {
+        MovePicker { first, cursor: MoveCursor::new(game, MoveOrder::Natural), sorted: None, started: false }
+    }
+
+    fn next(&mut self, game: &Game, ordering: Option<&MoveOrdering>, remaining_depth: usize) -> Option<Action> {
+        if !self.started {
+            self.started = true;
+            if self.first.is_some() {
+                return self.first
+            }
+        }
+
+        let Some(ordering) = ordering else {
+            return next_move(&mut self.cursor, game, self.first)
+        };
+        let sorted = self.sorted.get_or_insert_with(|| {
+            let mut actions = std::iter::from_fn(|| self.cursor.next_action(game)).filter(|&action| Some(action) != self.first).collect::<Vec<_>>();
+            ordering.sort(game, &mut actions, remaining_depth);
+            actions.into_iter()
+        });
+        sorted.next()
     }
+}
+
+// why a polling search stopped before returning a value
+enum Interrupt {
+    Missing(Vec<Vec<u8>>), // the keys to evaluate
+    OutOfBudget,
+}
+
+// the best move of an earlier search of the position, if it is still possible. A hash collision could give any move.
+fn tt_move(game: &Game, entry: Option<Entry>) -> Option<Action> {
+    entry?.best.filter(|&Action(from, to)| game.pieces_slice(game.player_to_move()).binary_search(&from).is_ok() && !game.has_piece(to))
+}
 
+// the line starting with `action` followed by the line of the position it leads to
+fn set_pv(pv: &mut Vec<Action>, action: Action, child_pv: Vec<Action>) {
+    pv.clear();
+    pv.push(action);
+    pv.extend(child_pv);
+}
+
+// the next generated move except `skip`, which was searched first
+fn next_move(moves: &mut MoveCursor, game: &Game, skip: Option<Action>) -> Option<Action> {
+    std::iter::from_fn(|| moves.next_action(game)).find(|&action| Some(action) != skip)
+}
+
+// the index of the best value for the player to move
+fn best_index(game: &Game, values: &[f64]) -> usize {
     if game.is_p1_moving_next() {
+        values.iter().enumerate().max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).unwrap().0
+    } else {
+        values.iter().enumerate().min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).unwrap().0
+    }
+}
+
+// a position that occurred before is scored as a draw, so the search seeks repetitions when behind and avoids them when ahead
+// None if the budget ran out. `pv` receives the expected line from this position when the value is within the window.
+fn _alphabeta(game: &mut Game, remaining_depth: usize, alpha: f64, beta: f64, search: &mut Search, tt: &mut TranspositionTable, pv: &mut Vec<Action>) -> Option<f64> {
+    if !search.visit() {
+        return None
+    }
+
+    if game.repetitions() > 0 {
+        return Some(0.)
+    }
+
+    if remaining_depth == 0 {
+        return Some(game.heuristic())
+    }
+
+    let hash = game.position_hash();
+    let entry = tt.probe(hash);
+    if let Some(value) = entry.and_then(|entry| entry.cutoff(remaining_depth, alpha, beta)) {
+        return Some(value)
+    }
+
+    // moves are generated lazily, so a cutoff by the stored best move skips the hop search. Without move ordering the
+    // generation stays lazy for the other moves too.
+    let mut moves = MovePicker::new(game, tt_move(game, entry));
+    let mut next = moves.next(game, search.ordering.as_ref(), remaining_depth);
+    if next.is_none() {
+        let value = game.heuristic();
+        tt.store(Entry { hash, depth: remaining_depth as _, bound: Bound::Exact, value, best: None });
+        return Some(value)
+    }
+
+    let mut best = None;
+    let value = if game.is_p1_moving_next() {
         let mut value = alpha;
 
-        for next_state in next_states {
-            value = value.max(_alphabeta(&next_state, remaining_depth - 1, value, beta));
+        while let Some(action) = next {
+            let mut child_pv = vec![];
+            let child = game.with_move(action, |next_state| _alphabeta(next_state, remaining_depth - 1, value, beta, search, tt, &mut child_pv))?;
+            if child > value || best.is_none() {
+                best = Some(action);
+                set_pv(pv, action, child_pv);
+            }
+            value = value.max(child);
             if value >= beta {
+                search.cutoff(action, remaining_depth);
                 break
             }
+            next = moves.next(game, search.ordering.as_ref(), remaining_depth);
         }
         value
     } else {
         let mut value = beta;
 
-        for next_state in next_states {
-            value = value.min(_alphabeta(&next_state, remaining_depth - 1, alpha, value));
+        while let Some(action) = next {
+            let mut child_pv = vec![];
+            let child = game.with_move(action, |next_state| _alphabeta(next_state, remaining_depth - 1, alpha, value, search, tt, &mut child_pv))?;
+            if child < value || best.is_none() {
+                best = Some(action);
+                set_pv(pv, action, child_pv);
+            }
+            value = value.min(child);
             if value <= alpha {
+                search.cutoff(action, remaining_depth);
                 break
             }
+            next = moves.next(game, search.ordering.as_ref(), remaining_depth);
         }
         value
+    };
+
+    tt.store(Entry { hash, depth: remaining_depth as _, bound: bound_of(value, alpha, beta), value, best });
+    Some(value)
+}
+
+// the value and the line that follows each root move
+type RootResults = Vec<(f64, Vec<Action>)>;
+
+// the best of root moves that were all searched with the full window: its index, value and the line that follows it
+fn best_result(game: &Game, mut results: RootResults) -> (usize, f64, Vec<Action>) {
+    let values = results.iter().map(|&(value, _)| value).collect::<Vec<_>>();
+    let i = best_index(game, &values);
+    let (value, line) = results.swap_remove(i);
+    (i, value, line)
+}
+
+// searches the root moves to `depth` and returns the best like best_result. None if the budget ran out.
+// With move ordering each move is searched with the window left by the better ones before it, so only the best value is exact.
+fn search_root(game: &Game, actions: &[Action], depth: usize, search: &mut Search, tt: &mut TranspositionTable) -> Option<(usize, f64, Vec<Action>)> {
+    let mut state = game.clone();
+    if search.ordering.is_none() {
+        let results = actions.iter().map(|&action| {
+            let mut pv = vec![];
+            let value = state.with_move(action, |next_state| _alphabeta(next_state, depth - 1, f64::NEG_INFINITY, f64::INFINITY, search, tt, &mut pv))?;
+            Some((value, pv))
+        }).collect::<Option<Vec<_>>>()?;
+        return Some(best_result(game, results))
+    }
+
+    let mut best: Option<(usize, f64, Vec<Action>)> = None;
+    for (i, &action) in actions.iter().enumerate() {
+        let (alpha, beta) = match best {
+            Some((_, value, _)) if game.is_p1_moving_next() => (value, f64::INFINITY),
+            Some((_, value, _)) => (f64::NEG_INFINITY, value),
+            None => (f64::NEG_INFINITY, f64::INFINITY),
+        };
+        let mut pv = vec![];
+        let value = state.with_move(action, |next_state| _alphabeta(next_state, depth - 1, alpha, beta, search, tt, &mut pv))?;
+        if best.is_none() || value > alpha && value < beta {
+            best = Some((i, value, pv));
+        }
     }
+    best
 }
 
-pub fn alphabeta(game: &Game, depth: usize) -> (Game, Action) {
-    let (next_states, actions) = game.expand(true);
-    let mut zipped: Vec<_> = next_states.into_iter().zip(actions.into_iter()).collect();
-    random_shuffle(&mut zipped);
+// the root moves in a random order, which varies the choice between equally good moves. With move ordering the
+// most advancing moves go first.
+fn root_moves(game: &Game, mut actions: Vec<Action>, search: &Search) -> Vec<Action> {
+    random_shuffle(&mut actions);
+    if let Some(ordering) = &search.ordering {
+        ordering.sort(game, &mut actions, usize::MAX); // no killers at the root
+    }
+    actions
+}
 
-    let values = zipped.iter().map(|(next_state, _)| _alphabeta(next_state, depth - 1, std::f64::NEG_INFINITY, std::f64::INFINITY)).collect::<Vec<_>>();
-    let i = if game.is_p1_moving_next() {
-        values.iter().enumerate().max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).unwrap().0
-    } else {
-        values.iter().enumerate().min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).unwrap().0
-    };
+// puts the best move of a completed depth first for the next one, if the moves are ordered
+fn promote(actions: &mut [Action], i: usize, search: &Search) {
+    if search.ordering.is_some() {
+        actions[..=i].rotate_right(1);
+    }
+}
+
+fn report(actions: &[Action], (i, score, line): (usize, f64, Vec<Action>), nodes: usize, depth: usize) -> SearchReport {
+    let mut pv = vec![];
+    set_pv(&mut pv, actions[i], line);
+    SearchReport { action: actions[i], score, pv, nodes, depth }
+}
 
-    zipped.swap_remove(i)
+// `tt` may be reused by later searches on the same board and ruleset. `ordering` enables the move ordering (see MoveOrdering),
+// which only changes the number of nodes searched and the choice between equally good moves.
+pub fn alphabeta<'a>(game: &Game<'a>, depth: usize, ordering: bool, tt: &mut TranspositionTable) -> (Game<'a>, Action) {
+    let SearchReport { action, .. } = alphabeta_report(game, depth, ordering, tt);
+    (game.move_to(action.0, action.1), action)
 }
 
-fn _alphabeta_poll(game: &Game, remaining_depth: usize, forward_only: bool, alpha: f64, beta: f64, score_map: &BTreeMap<Vec<u8>, f64>) -> Result<f64, Vec<Vec<u8>>> {
-    if remaining_depth <= 0 {
-        return score_map.get(&game.key()).copied().ok_or_else(|| vec![game.key().clone()])
+// alphabeta that also reports the score and the expected line of the chosen move
+pub fn alphabeta_report(game: &Game, depth: usize, ordering: bool, tt: &mut TranspositionTable) -> SearchReport {
+    let mut search = Search::unlimited(game, ordering);
+    let actions = root_moves(game, game.actions(), &search);
+    let best = search_root(game, &actions, depth, &mut search, tt).unwrap();
+    report(&actions, best, search.nodes, depth)
+}
+
+// iterative deepening: searches depth 1, 2, ... until the budget runs out, and plays the best move of the last completed depth.
+// The report is of that depth, except for the nodes which include the interrupted depth. The deeper searches reuse the results
+// of the shallower ones through `tt`.
+pub fn alphabeta_timed<'a>(game: &Game<'a>, budget: Budget, ordering: bool, tt: &mut TranspositionTable) -> (Game<'a>, SearchReport) {
+    let mut search = Search::new(budget, game, ordering);
+    let mut actions = root_moves(game, game.actions(), &search);
+    let mut best = None;
+    for depth in 1..=budget.max_depth {
+        let Some(result) = search_root(game, &actions, depth, &mut search, tt) else {
+            break
+        };
+        let i = result.0;
+        best = Some(report(&actions, result, search.nodes, depth));
+        promote(&mut actions, i, &search);
+
+        search.enforced = true;
+        if search.out_of_budget() {
+            break
+        }
     }
 
-    let (next_states, _) = if forward_only {
-        game.expand_forward_only(false)
+    let mut best = best.unwrap();
+    best.nodes = search.nodes;
+    (game.move_to(best.action.0, best.action.1), best)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn _alphabeta_poll(game: &mut Game, remaining_depth: usize, forward_only: bool, canonical: bool, alpha: f64, beta: f64, score_map: &ScoreCache, search: &mut Search, tt: &mut TranspositionTable, pv: &mut Vec<Action>) -> Result<f64, Interrupt> {
+    if !search.visit() {
+        return Err(Interrupt::OutOfBudget)
+    }
+
+    if game.repetitions() > 0 {
+        return Ok(0.5)
+    }
+
+    if remaining_depth == 0 {
+        return lookup_score(game, canonical, score_map).map_err(|key| Interrupt::Missing(vec![key]))
+    }
+
+    // only complete results are stored, so they stay valid while the client adds scores
+    let hash = game.position_hash();
+    let entry = tt.probe(hash);
+    if let Some(value) = entry.and_then(|entry| entry.cutoff(remaining_depth, alpha, beta)) {
+        return Ok(value)
+    }
+
+    let mut actions = if forward_only {
+        game.actions_forward_only()
     } else {
-        game.expand(false)
+        game.actions()
     };
-    if next_states.is_empty() {
-        return score_map.get(&game.key()).copied().ok_or_else(|| vec![game.key().clone()])
+    if actions.is_empty() {
+        let value = lookup_score(game, canonical, score_map).map_err(|key| Interrupt::Missing(vec![key]))?;
+        tt.store(Entry { hash, depth: remaining_depth as _, bound: Bound::Exact, value, best: None });
+        return Ok(value)
+    }
+    if let Some(ordering) = &search.ordering {
+        ordering.sort(game, &mut actions, remaining_depth);
+    }
+    if let Some(i) = entry.and_then(|entry| entry.best).and_then(|first| actions.iter().position(|&action| action == first)) {
+        actions[..=i].rotate_right(1);
     }
 
-    if game.is_p1_moving_next() {
+    let mut best = None;
+    let value = if game.is_p1_moving_next() {
         let mut value = alpha;
 
-        for next_state in next_states {
-            value = value.max(_alphabeta_poll(&next_state, remaining_depth - 1, forward_only, value, beta, score_map)?);
+        for action in actions {
+            let mut child_pv = vec![];
+            let child = game.with_move(action, |next_state| _alphabeta_poll(next_state, remaining_depth - 1, forward_only, canonical, value, beta, score_map, search, tt, &mut child_pv))?;
+            if child > value || best.is_none() {
+                best = Some(action);
+                set_pv(pv, action, child_pv);
+            }
+            value = value.max(child);
             if value >= beta {
+                search.cutoff(action, remaining_depth);
                 break
             }
         }
-        Ok(value)
+        value
     } else {
         let mut value = beta;
 
-        for next_state in next_states {
-            value = value.min(_alphabeta_poll(&next_state, remaining_depth - 1, forward_only, alpha, value, score_map)?);
+        for action in actions {
+            let mut child_pv = vec![];
+            let child = game.with_move(action, |next_state| _alphabeta_poll(next_state, remaining_depth - 1, forward_only, canonical, alpha, value, score_map, search, tt, &mut child_pv))?;
+            if child < value || best.is_none() {
+                best = Some(action);
+                set_pv(pv, action, child_pv);
+            }
+            value = value.min(child);
             if value <= alpha {
+                search.cutoff(action, remaining_depth);
                 break
             }
         }
-        Ok(value)
-    }
+        value
+    };
+
+    tt.store(Entry { hash, depth: remaining_depth as _, bound: bound_of(value, alpha, beta), value, best });
+    Ok(value)
 }
 
-pub fn alphabeta_poll(game: &Game, depth: usize, forward_only: bool, score_map: &BTreeMap<Vec<u8>, f64>) -> Result<(Game, Action), Vec<Vec<u8>>> {
-    let (next_states, actions) = if forward_only {
-        game.expand_forward_only(true)
+#[allow(clippy::too_many_arguments)]
+pub fn alphabeta_poll<'a>(game: &Game<'a>, depth: usize, forward_only: bool, canonical: bool, ordering: bool, score_map: &ScoreCache, tt: &mut TranspositionTable) -> Result<(Game<'a>, Action), Vec<Vec<u8>>> {
+    let mut search = Search::unlimited(game, ordering);
+    let mut actions = root_moves(game, if forward_only {
+        game.actions_forward_only()
     } else {
-        game.expand(true)
-    };
+        game.actions()
+    }, &search);
 
-    let mut zipped: Vec<_> = next_states.into_iter().zip(actions.into_iter()).collect();
-    random_shuffle(&mut zipped);
+    let (i, _, _) = best_result(game, search_root_poll(game, &actions, depth, forward_only, canonical, score_map, &mut search, tt).unwrap()?);
+    let action = actions.swap_remove(i);
+    Ok((game.move_to(action.0, action.1), action))
+}
 
-    let values = zipped.iter().map(|(next_state, _)| _alphabeta_poll(next_state, depth - 1, forward_only, std::f64::NEG_INFINITY, std::f64::INFINITY, score_map)).collect::<Vec<_>>();
-    let i = if game.is_p1_moving_next() {
-        values.iter().enumerate().max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).unwrap().0
-    } else {
-        values.iter().enumerate().min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).unwrap().0
-    };
+// searches the root moves to `depth` with the full window like search_root without move ordering. Err with the keys missing
+// from all root moves, so the client evaluates them in one go. None if the budget ran out.
+#[allow(clippy::too_many_arguments)]
+fn search_root_poll(game: &Game, actions: &[Action], depth: usize, forward_only: bool, canonical: bool, score_map: &ScoreCache, search: &mut Search, tt: &mut TranspositionTable) -> Option<Result<RootResults, Vec<Vec<u8>>>> {
+    let mut state = game.clone();
+    let mut results = vec![];
+    let mut missing = vec![];
+    for &action in actions {
+        let mut pv = vec![];
+        match state.with_move(action, |next_state| _alphabeta_poll(next_state, depth - 1, forward_only, canonical, f64::NEG_INFINITY, f64::INFINITY, score_map, search, tt, &mut pv)) {
+            Ok(value) => results.push((value, pv)),
+            Err(Interrupt::Missing(keys)) => missing.extend(keys),
+            Err(Interrupt::OutOfBudget) => return None,
+        }
+    }
+
+    Some(if missing.is_empty() { Ok(results) } else { Err(missing) })
+}
+
+// the state of alphabeta_timed_poll between calls. The clock keeps running while the client evaluates the keys.
+pub struct TimedSession {
+    pub score_map: ScoreCache,
+    tt: TranspositionTable, // kept between calls, see _alphabeta_poll
+    search: Search,
+    actions: Vec<Action>, // the root moves (see root_moves), empty before the first call
+    depth: usize, // the depth in progress
+    best: Option<SearchReport>, // the report of the last completed depth
+}
+
+pub fn new_timed_session(game: &Game, budget: Budget, ordering: bool, check_collisions: bool, tt_size: usize) -> TimedSession {
+    TimedSession { score_map: ScoreCache::new(check_collisions), tt: TranspositionTable::new(tt_size), search: Search::new(budget, game, ordering), actions: vec![], depth: 1, best: None }
+}
+
+// alphabeta_timed with the scores from the session. Each depth is retried until all its keys are evaluated,
+// the game must not change between calls.
+pub fn alphabeta_timed_poll<'a>(game: &Game<'a>, forward_only: bool, canonical: bool, sess: &mut TimedSession) -> Result<(Game<'a>, SearchReport), Vec<Vec<u8>>> {
+    if sess.actions.is_empty() {
+        sess.actions = root_moves(game, if forward_only {
+            game.actions_forward_only()
+        } else {
+            game.actions()
+        }, &sess.search);
+    }
+
+    while sess.depth <= sess.search.budget.max_depth && !sess.search.out_of_budget() {
+        let depth = sess.depth;
+        let Some(results) = search_root_poll(game, &sess.actions, depth, forward_only, canonical, &sess.score_map, &mut sess.search, &mut sess.tt) else {
+            break
+        };
+
+        let result = best_result(game, results?);
+        let i = result.0;
+        sess.best = Some(report(&sess.actions, result, sess.search.nodes, depth));
+        promote(&mut sess.actions, i, &sess.search);
+        sess.depth += 1;
+        sess.search.enforced = true;
+    }
+
+    let mut best = sess.best.clone().unwrap();
+    best.nodes = sess.search.nodes;
+    Ok((game.move_to(best.action.0, best.action.1), best))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::builtin_board, game::raced_positions};
 
-    Ok(zipped.swap_remove(i))
+    #[test]
+    fn the_transposition_table_does_not_change_the_scores() {
+        let board = builtin_board(3, false, 2).unwrap();
+        for game in raced_positions(board, 12) {
+            for ordering in [false, true] {
+                let without = alphabeta_report(&game, 3, ordering, &mut TranspositionTable::new(0));
+                let with = alphabeta_report(&game, 3, ordering, &mut TranspositionTable::new(1 << 12));
+                assert_eq!(with.score, without.score, "turn {} ordering {ordering}", game.turn);
+
+                let budget = Budget { millis: None, nodes: None, max_depth: 3 };
+                let (_, timed) = alphabeta_timed(&game, budget, ordering, &mut TranspositionTable::new(1 << 12));
+                assert_eq!((timed.score, timed.depth), (without.score, 3), "turn {} ordering {ordering}", game.turn);
+            }
+        }
+    }
+
+    #[test]
+    fn alphabeta_poll_asks_for_the_keys_of_all_root_moves() {
+        let board = builtin_board(3, false, 2).unwrap();
+        let game = raced_positions(board, 4).pop().unwrap();
+        let mut score_map = ScoreCache::new(false);
+        let evaluate = |score_map: &mut ScoreCache, keys: &[Vec<u8>]| for key in keys {
+            score_map.insert(game.key_hash_of(key), key.clone(), 0.5 + Game::from_key(&game, key).unwrap().heuristic() / 100.);
+        };
+
+        let Err(mut keys) = alphabeta_poll(&game, 1, false, false, true, &ScoreCache::new(false), &mut TranspositionTable::new(1 << 12)) else {
+            panic!("no scores are known")
+        };
+        let mut expected: Vec<_> = game.actions().iter().map(|&Action(from, to)| game.move_to(from, to).key()).collect();
+        keys.sort();
+        expected.sort();
+        assert_eq!(keys, expected);
+        evaluate(&mut score_map, &keys);
+
+        let (_, action) = alphabeta_poll(&game, 1, false, false, true, &score_map, &mut TranspositionTable::new(1 << 12)).unwrap();
+        let best = game.actions().into_iter().map(|Action(from, to)| game.move_to(from, to).heuristic()).fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!(game.move_to(action.0, action.1).heuristic(), best);
+
+        while let Err(keys) = alphabeta_poll(&game, 2, false, false, true, &score_map, &mut TranspositionTable::new(1 << 12)) {
+            assert!(!keys.is_empty());
+            evaluate(&mut score_map, &keys);
+        }
+    }
+
+    #[test]
+    fn reports_follow_a_legal_line_to_their_score() {
+        let board = builtin_board(3, false, 2).unwrap();
+        for game in raced_positions(board, 10) {
+            for ordering in [false, true] {
+                // without a transposition table the line is never cut short
+                let report = alphabeta_report(&game, 3, ordering, &mut TranspositionTable::new(0));
+                assert_eq!((report.depth, report.pv.len(), report.pv[0]), (3, 3, report.action));
+                let end = report.pv.iter().fold(game.clone(), |state, &Action(from, to)| state.try_move(from, to).unwrap());
+                assert_eq!(end.heuristic(), report.score, "turn {} ordering {ordering}", game.turn);
+                assert!(report.nodes > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn timed_reports_count_the_nodes_of_every_depth() {
+        let board = builtin_board(3, false, 2).unwrap();
+        let game = raced_positions(board, 6).pop().unwrap();
+        // without move ordering and transposition table the searches of each depth are independent
+        let nodes: Vec<_> = (1..=3).map(|depth| alphabeta_report(&game, depth, false, &mut TranspositionTable::new(0)).nodes).collect();
+
+        let budget = Budget { millis: None, nodes: None, max_depth: 3 };
+        let (_, report) = alphabeta_timed(&game, budget, false, &mut TranspositionTable::new(0));
+        assert_eq!((report.depth, report.nodes), (3, nodes.iter().sum()));
+
+        let budget = Budget { millis: None, nodes: Some(nodes[0] + nodes[1] + 10), max_depth: 3 };
+        let (_, report) = alphabeta_timed(&game, budget, false, &mut TranspositionTable::new(0));
+        assert_eq!(report.depth, 2);
+        assert!(report.nodes > nodes[0] + nodes[1] && report.nodes <= budget.nodes.unwrap() + 1, "{} nodes", report.nodes);
+    }
 }
@@ -0,0 +1,64 @@
+use std::ops::{BitAnd, BitOr};
+
+use crate::Position;
+
+// a set of positions. 256 bits cover the largest board (253 cells).
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Bitboard([u64; 4]);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard([0; 4]);
+
+    pub fn from_positions(positions: &[Position]) -> Bitboard {
+        let mut result = Bitboard::EMPTY;
+        for &pos in positions {
+            result.insert(pos);
+        }
+        result
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        self.0[pos as usize >> 6] >> (pos & 63) & 1 != 0
+    }
+
+    pub fn insert(&mut self, pos: Position) {
+        self.0[pos as usize >> 6] |= 1 << (pos & 63)
+    }
+
+    pub fn remove(&mut self, pos: Position) {
+        self.0[pos as usize >> 6] &= !(1 << (pos & 63))
+    }
+
+    pub fn without(mut self, pos: Position) -> Bitboard {
+        self.remove(pos);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    pub fn is_subset(&self, other: &Bitboard) -> bool {
+        *self & *other == *self
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Bitboard) -> Bitboard {
+        Bitboard([self.0[0] & rhs.0[0], self.0[1] & rhs.0[1], self.0[2] & rhs.0[2], self.0[3] & rhs.0[3]])
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard([self.0[0] | rhs.0[0], self.0[1] | rhs.0[1], self.0[2] | rhs.0[2], self.0[3] | rhs.0[3]])
+    }
+}
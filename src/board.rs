@@ -1,131 +1,734 @@
-use crate::Position;
+use std::{collections::{BTreeMap, VecDeque}, sync::{LazyLock, Mutex}};
+
+use serde_json::Value as JsonValue;
+
+use crate::{INVALID_POSITION, Position, bitboard::Bitboard};
+
+// the axial coordinate offsets of the six directions, in the same order as the ajd_matrix.
+// q goes to the right and r goes to the upper right, so the cube coordinates are (q, r, -q-r).
+pub const DIRECTIONS: [(i32, i32); 6] = [(-1, 1), (0, 1), (1, 0), (1, -1), (0, -1), (-1, 0)];
 
 pub struct Board {
-    pub n_pieces: usize,
+    pub name: String,
+    pub n_players: usize,
+    pub n_pieces: usize, // the number of pieces of each player
     pub board_size: usize,
-    pub ajd_matrix: &'static [[Position; 6]],
-    pub p1_base: &'static [Position],
-    pub p2_base: &'static [Position],
-    pub p1_distance_map: &'static [u64],
-    pub p2_distance_map: &'static [u64],
-    pub min_distance: u64,
+    pub ajd_matrix: Vec<[Position; 6]>, // the neibours of each position, in the order of UL, UR, R, LR, LL, L
+    pub coordinates: Vec<(i32, i32)>, // axial coordinates (q, r) of each position, see DIRECTIONS
+    pub bases: Vec<Vec<Position>>, // the starting positions of each player, in the order of moving
+    pub targets: Vec<Vec<Position>>, // the positions each player needs to occupy, i.e. the opposite corner
+    pub distance_maps: Vec<Vec<u64>>, // distance of each position to the tip of the target of each player
+    pub min_distances: Vec<u64>, // the distance sum when all pieces of a player reached the target
+    pub symmetries: Vec<Symmetry>, // the transforms that map the board and its players onto themselves. The first is the identity.
+    pub rays: Vec<[Vec<Position>; 6]>, // the positions from each position to the edge in each direction, nearest first
+    pub ray_masks: Vec<[Bitboard; 6]>, // the same positions as rays, as sets
+    pub target_masks: Vec<Bitboard>, // the targets as sets
+    pub zobrist_pieces: Vec<Vec<u64>>, // the random number of a piece of each player at each position
+    pub zobrist_seats: Vec<u64>, // the random number of each player to move
+    pub fingerprint: u32, // identifies the layout of the board in game keys
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum BoardError {
+    Definition(String), // the json board definition is malformed
+    Size { board_size: usize },
+    NeibourOutOfBoard { pos: Position, direction: usize, neibour: Position },
+    AsymmetricAdjacency { pos: Position, direction: usize, neibour: Position },
+    Coordinates { pos: Position, direction: usize, neibour: Position },
+    DuplicatedCoordinates { pos: Position, other: Position },
+    PlayerCount { n_players: usize },
+    BaseSize { player: usize, size: usize },
+    TargetSize { player: usize, size: usize },
+    PositionOutOfBoard { player: usize, pos: Position },
+    DuplicatedPosition { player: usize, pos: Position },
+    Unsorted { player: usize },
+    OverlappingBases { pos: Position },
+    DistanceMapSize { player: usize, size: usize },
+    UnreachableTarget { player: usize, pos: Position },
+    NoPathToTarget { player: usize, pos: Position }, // the pieces of the player can reach pos, but not the target from there
+    DistanceMap { player: usize, pos: Position, expected: u64, actual: u64 },
+    MinDistance { player: usize, expected: u64, actual: u64 },
+    TargetNotMinimal { player: usize, pos: Position },
+}
+
+impl std::fmt::Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BoardError::Definition(message) => write!(f, "invalid board definition: {message}"),
+            BoardError::Size { board_size } => write!(f, "the board has {board_size} positions, but it needs 1 to {INVALID_POSITION} positions, each with neibours and coordinates"),
+            BoardError::NeibourOutOfBoard { pos, direction, neibour } => write!(f, "neibour {direction} of position {pos} is {neibour}, which is outside of the board"),
+            BoardError::AsymmetricAdjacency { pos, direction, neibour } => write!(f, "neibour {direction} of position {pos} is {neibour}, but the opposite neibour of {neibour} is not {pos}"),
+            BoardError::Coordinates { pos, direction, neibour } => write!(f, "neibour {direction} of position {pos} is {neibour}, but their coordinates do not differ by {:?}", DIRECTIONS[*direction]),
+            BoardError::DuplicatedCoordinates { pos, other } => write!(f, "positions {other} and {pos} have the same coordinates"),
+            BoardError::PlayerCount { n_players } => write!(f, "there must be at least 2 players, and a base, target, distance map and min distance for each of the {n_players} players"),
+            BoardError::BaseSize { player, size } => write!(f, "the base of player {player} has {size} positions, but all bases and targets need the same non-zero size"),
+            BoardError::TargetSize { player, size } => write!(f, "the target of player {player} has {size} positions, but all bases and targets need the same non-zero size"),
+            BoardError::PositionOutOfBoard { player, pos } => write!(f, "position {pos} of player {player} is outside of the board"),
+            BoardError::DuplicatedPosition { player, pos } => write!(f, "position {pos} appears twice in the base or target of player {player}"),
+            BoardError::Unsorted { player } => write!(f, "the base and target of player {player} must be sorted"),
+            BoardError::OverlappingBases { pos } => write!(f, "position {pos} is in more than one base"),
+            BoardError::DistanceMapSize { player, size } => write!(f, "the distance map of player {player} has {size} entries instead of one for each position"),
+            BoardError::UnreachableTarget { player, pos } => write!(f, "position {pos} in the target of player {player} is unreachable"),
+            BoardError::NoPathToTarget { player, pos } => write!(f, "the pieces of player {player} can reach position {pos}, but there is no path from it to their target"),
+            BoardError::DistanceMap { player, pos, expected, actual } => write!(f, "the distance of position {pos} for player {player} is {actual}, but the BFS distance to the tip of the target is {expected}"),
+            BoardError::MinDistance { player, expected, actual } => write!(f, "the min distance of player {player} is {actual}, but the distance sum of the target is {expected}"),
+            BoardError::TargetNotMinimal { player, pos } => write!(f, "position {pos} is not in the target of player {player}, but it is not farther than some target positions"),
+        }
+    }
+}
+
+// a rotation or reflection of the board, possibly together with a rotation of the seats of the players
+pub struct Symmetry {
+    pub permutation: Vec<Position>, // the image of each position
+    pub player_shift: usize, // player i becomes player (i + player_shift) % n_players
+}
+
+impl Symmetry {
+    pub fn apply(&self, pos: Position) -> Position {
+        if pos == INVALID_POSITION {
+            return INVALID_POSITION
+        }
+        self.permutation[pos as usize]
+    }
+
+    pub fn inverse(&self, n_players: usize) -> Symmetry {
+        let mut permutation = vec![INVALID_POSITION; self.permutation.len()];
+        for (pos, &image) in self.permutation.iter().enumerate() {
+            permutation[image as usize] = pos as _;
+        }
+        Symmetry { permutation, player_shift: (n_players - self.player_shift) % n_players }
+    }
 }
 
 impl Board {
+    pub fn from_rank(rank: usize, plus: bool) -> Board {
+        Board::from_rank_with_players(rank, plus, 2)
+    }
+
+    // a port of scripts/gen_board.jl. `rank` is the number of rows in each corner of the star.
+    // `plus` enlarges the bases with an extra row and the two neighbouring corners.
+    // Players sit at every corner (6), skip every other corner (3), or leave two opposite corners empty (4).
+    pub fn from_rank_with_players(rank: usize, plus: bool, n_players: usize) -> Board {
+        assert!((1..=6).contains(&rank), "rank must be in 1..=6, otherwise positions do not fit in u8");
+        assert!(matches!(n_players, 2 | 3 | 4 | 6), "only 2, 3, 4 or 6 players are supported");
+        assert!(!plus || n_players <= 3, "plus bases of neighbouring corners overlap");
+        let r = rank as i32;
+
+        // x axis goes from the lower left corner to the horizonal right, y axis goes to the upper right
+        let mut nodes: Vec<(i32, i32)> = vec![];
+
+        // the main triangle including the lower left, top, and lower right corners
+        for i in 0..=3*r {
+            for j in 0..=3*r-i {
+                nodes.push((i, j))
+            }
+        }
+
+        for i in 1..=r {
+            for j in 1..=r {
+                if j >= i {
+                    nodes.push((-i, r+j)); // the upper left corner
+                    nodes.push((r+j, -i)); // the bottom corner
+                }
+                if i + j > r {
+                    nodes.push((r+i, r+j)); // the upper right corner
+                }
+            }
+        }
+
+        // label the nodes row by row, which is the same order as sorting by the cartesian coordinates
+        nodes.sort_by_key(|&(x, y)| (y, x));
+        let board_size = nodes.len();
+
+        let find = |x: i32, y: i32| nodes.binary_search_by_key(&(y, x), |&(x, y)| (y, x)).map(|i| i as Position).unwrap_or(INVALID_POSITION);
+        let ajd_matrix: Vec<[Position; 6]> = nodes.iter().map(|&(x, y)| {
+            [find(x-1, y+1), find(x, y+1), find(x+1, y), find(x+1, y-1), find(x, y-1), find(x-1, y)]
+        }).collect();
+
+        // the six corners counter-clockwise from the bottom one, so corner i is opposite to corner (i+3)%6
+        let corner_of = |(x, y): (i32, i32)| {
+            if y < 0 { 0 } else if x > 2*r { 1 } else if x + y > 3*r { 2 } else if y > 2*r { 3 } else if x < 0 { 4 } else if x + y < r { 5 } else { 6 } // 6 is the central hexagon
+        };
+        let tips = [find(2*r, -r), find(3*r, 0), find(2*r, 2*r), find(0, 3*r), find(-r, 2*r), find(0, 0)];
+        let corners: Vec<Vec<Position>> = (0..6).map(|c| {
+            let corner = (0..board_size).filter(|&i| corner_of(nodes[i]) == c).map(|i| i as Position).collect::<Vec<_>>();
+            if plus { base_plus(&ajd_matrix, &corner) } else { corner }
+        }).collect();
+
+        let seats: &[usize] = match n_players {
+            2 => &[0, 3],
+            3 => &[0, 2, 4],
+            4 => &[0, 1, 3, 4],
+            _ => &[0, 1, 2, 3, 4, 5],
+        };
+        let bases: Vec<_> = seats.iter().map(|&c| corners[c].clone()).collect();
+        let targets: Vec<_> = seats.iter().map(|&c| corners[(c + 3) % 6].clone()).collect();
+        let distance_maps: Vec<_> = seats.iter().map(|&c| bfs_distance(&ajd_matrix, tips[(c + 3) % 6])).collect();
+        let min_distances = min_distances(&targets, &distance_maps);
+
+        let mut name = match rank {
+            2 => "tiny".to_string(),
+            3 => "small".to_string(),
+            4 => "standard".to_string(),
+            5 => "large".to_string(),
+            6 => "huge".to_string(),
+            _ => format!("rank {rank}")
+        };
+        if plus {
+            name.push('+')
+        }
+        if n_players != 2 {
+            name.push_str(&format!(" ({n_players} players)"))
+        }
+
+        let coordinates = nodes.iter().map(|&(x, y)| (x - r, y - r)).collect(); // centered at the middle of the star
+
+        let board = Board { name, n_players, n_pieces: bases[0].len(), board_size, ajd_matrix, coordinates, bases, targets, distance_maps, min_distances, symmetries: vec![], rays: vec![], ray_masks: vec![], target_masks: vec![], zobrist_pieces: vec![], zobrist_seats: vec![], fingerprint: 0 };
+        match board.checked() {
+            Ok(board) => board,
+            Err(e) => panic!("generated an invalid board: {e}")
+        }
+    }
+
+    // Load a board from a JSON object of the following form. Positions are indices into "adjacency".
+    // {
+    //     "name": "truncated standard",
+    //     "adjacency": [[1, 2, null, null, null, null], ...], // the neibours of each position in the order of UL, UR, R, LR, LL, L. null or 255 for none.
+    //     "bases": [[0, 1, 2], [34, 35, 36]], // the starting positions of each player in the order of moving. All bases have the same size.
+    //     "targets": [[34, 35, 36], [0, 1, 2]], // optional. Defaults to the base of the opposite player, i.e. (i + n_players / 2) % n_players.
+    //     "distance_maps": [[8, 7, ...], [0, 1, ...]], // optional. The BFS distance to the target position that is the farthest from the base, which
+    //                                                  // is also the default. Only a check of exported boards, as validate rejects any other map.
+    //     "coordinates": [[0, -4], ...] // optional. The axial coordinates of each position. Defaults to walking the adjacency from position 0 at [0, 0].
+    // }
+    // A player finishes when every position of its target is occupied, at least one of them by its own pieces (see
+    // Game::is_finished). Opponents' pieces left in the target then block it instead of stalling the game.
+    pub fn from_json(json: &JsonValue) -> Result<Board, BoardError> {
+        Board::parse_json(json).map_err(BoardError::Definition)?.checked()
+    }
+
+    fn parse_json(json: &JsonValue) -> Result<Board, String> {
+        let name = json["name"].as_str().ok_or("\"name\" must be a string")?.to_string();
+
+        let ajd_matrix = json["adjacency"].as_array().ok_or("\"adjacency\" must be an array")?.iter().enumerate().map(|(i, row)| {
+            let row = row.as_array().filter(|row| row.len() == 6).ok_or(format!("adjacency of position {i} must be an array of 6 neibours"))?;
+            let mut neibours = [INVALID_POSITION; 6];
+            for (direction, neibour) in row.iter().enumerate() {
+                if !neibour.is_null() {
+                    neibours[direction] = neibour.as_u64().filter(|&n| n <= INVALID_POSITION as u64).ok_or(format!("neibour {direction} of position {i} must be a position, null or 255"))? as _;
+                }
+            }
+            Ok(neibours)
+        }).collect::<Result<Vec<_>, String>>()?;
+        let board_size = ajd_matrix.len();
+        if board_size == 0 || board_size > INVALID_POSITION as usize {
+            return Err(format!("the board must have 1 to {} positions", INVALID_POSITION))
+        }
+        if let Some(i) = ajd_matrix.iter().position(|row| row.iter().any(|&n| n != INVALID_POSITION && n as usize >= board_size)) {
+            return Err(format!("position {i} has a neibour outside of the board"))
+        }
+
+        let parse_lists = |key: &str| -> Result<Vec<Vec<JsonValue>>, String> {
+            json[key].as_array().ok_or(format!("\"{key}\" must be an array"))?.iter()
+                .map(|list| list.as_array().cloned().ok_or(format!("\"{key}\" must be an array of arrays")))
+                .collect()
+        };
+        let parse_positions = |key: &str| -> Result<Vec<Vec<Position>>, String> {
+            parse_lists(key)?.iter().map(|list| {
+                let mut positions = list.iter().map(|p| p.as_u64().filter(|&p| (p as usize) < board_size).map(|p| p as Position))
+                    .collect::<Option<Vec<_>>>().ok_or(format!("\"{key}\" contains positions outside of the board"))?;
+                positions.sort_unstable();
+                Ok(positions)
+            }).collect()
+        };
+
+        let bases = parse_positions("bases")?;
+        let n_players = bases.len();
+        if !(2..=6).contains(&n_players) {
+            return Err("there must be 2 to 6 bases".to_string())
+        }
+        let n_pieces = bases[0].len();
+
+        let targets = if json["targets"].is_null() {
+            if n_players % 2 == 1 {
+                return Err("\"targets\" is required for odd number of players".to_string())
+            }
+            (0..n_players).map(|i| bases[(i + n_players / 2) % n_players].clone()).collect()
+        } else {
+            parse_positions("targets")?
+        };
+
+        let distance_maps: Vec<Vec<u64>> = if json["distance_maps"].is_null() {
+            bases.iter().zip(&targets).map(|(base, target)| tip_distance(&ajd_matrix, base, target)).collect()
+        } else {
+            parse_lists("distance_maps")?.iter().map(|list| list.iter().map(|d| d.as_u64()).collect::<Option<Vec<_>>>().ok_or("\"distance_maps\" must contain non-negative integers".to_string())).collect::<Result<_, _>>()?
+        };
+
+        let coordinates = if json["coordinates"].is_null() {
+            walk_coordinates(&ajd_matrix).ok_or("the board must be connected to derive the coordinates")?
+        } else {
+            parse_lists("coordinates")?.iter().map(|c| match c.as_slice() {
+                [q, r] => Some((q.as_i64()? as i32, r.as_i64()? as i32)),
+                _ => None
+            }).collect::<Option<Vec<_>>>().filter(|c| c.len() == board_size).ok_or("\"coordinates\" must be a pair of integers for each position")?
+        };
+
+        let min_distances = min_distances(&targets, &distance_maps);
+        Ok(Board { name, n_players, n_pieces, board_size, ajd_matrix, coordinates, bases, targets, distance_maps, min_distances, symmetries: vec![], rays: vec![], ray_masks: vec![], target_masks: vec![], zobrist_pieces: vec![], zobrist_seats: vec![], fingerprint: 0 })
+    }
+
+    // validate the board and fill in the symmetries, rays and masks, which assume a valid board
+    fn checked(mut self) -> Result<Board, BoardError> {
+        self.validate()?;
+        self.symmetries = find_symmetries(&self);
+        self.rays = (0..self.board_size).map(|pos| std::array::from_fn(|direction| {
+            let mut ray = vec![];
+            let mut cp = self.ajd_matrix[pos][direction];
+            while cp != INVALID_POSITION {
+                ray.push(cp);
+                cp = self.ajd_matrix[cp as usize][direction];
+            }
+            ray
+        })).collect();
+        self.ray_masks = self.rays.iter().map(|rays| std::array::from_fn(|direction| Bitboard::from_positions(&rays[direction]))).collect();
+        self.target_masks = self.targets.iter().map(|target| Bitboard::from_positions(target)).collect();
+
+        // fixed seed, so that hashes are the same in every run
+        let mut seed = 0x2545f4914f6cdd1d;
+        self.zobrist_pieces = (0..self.n_players).map(|_| (0..self.board_size).map(|_| splitmix64(&mut seed)).collect()).collect();
+        self.zobrist_seats = (0..self.n_players).map(|_| splitmix64(&mut seed)).collect();
+
+        // FNV-1a over everything that makes positions mean the same thing, the name is left out
+        let layout = [self.board_size, self.n_players, self.n_pieces].into_iter().map(|n| n as u8)
+            .chain(self.ajd_matrix.iter().flatten().copied())
+            .chain(self.bases.iter().chain(&self.targets).flatten().copied());
+        self.fingerprint = fnv1a(0x811c9dc5, layout);
+        Ok(self)
+    }
+
+    pub fn validate(&self) -> Result<(), BoardError> {
+        let board_size = self.board_size;
+        if board_size == 0 || board_size > INVALID_POSITION as usize || self.ajd_matrix.len() != board_size || self.coordinates.len() != board_size {
+            return Err(BoardError::Size { board_size })
+        }
+
+        for pos in 0..board_size {
+            for (direction, &neibour) in self.ajd_matrix[pos].iter().enumerate() {
+                if neibour == INVALID_POSITION {
+                    continue
+                }
+                if neibour as usize >= board_size {
+                    return Err(BoardError::NeibourOutOfBoard { pos: pos as _, direction, neibour })
+                }
+                if self.ajd_matrix[neibour as usize][(direction + 3) % 6] != pos as Position {
+                    return Err(BoardError::AsymmetricAdjacency { pos: pos as _, direction, neibour })
+                }
+                let ((q, r), (dq, dr)) = (self.coordinates[pos], DIRECTIONS[direction]);
+                if self.coordinates[neibour as usize] != (q + dq, r + dr) {
+                    return Err(BoardError::Coordinates { pos: pos as _, direction, neibour })
+                }
+            }
+            if let Some(other) = self.coordinates[..pos].iter().position(|&c| c == self.coordinates[pos]) {
+                return Err(BoardError::DuplicatedCoordinates { pos: pos as _, other: other as _ })
+            }
+        }
+
+        let n_players = self.n_players;
+        if n_players < 2 || [self.bases.len(), self.targets.len(), self.distance_maps.len(), self.min_distances.len()].iter().any(|&n| n != n_players) {
+            return Err(BoardError::PlayerCount { n_players })
+        }
+
+        let mut occupied = vec![false; board_size];
+        for player in 0..n_players {
+            for (positions, is_base) in [(&self.bases[player], true), (&self.targets[player], false)] {
+                if self.n_pieces == 0 || positions.len() != self.n_pieces {
+                    let size = positions.len();
+                    return Err(if is_base { BoardError::BaseSize { player, size } } else { BoardError::TargetSize { player, size } })
+                }
+                if let Some(&pos) = positions.iter().find(|&&pos| pos as usize >= board_size) {
+                    return Err(BoardError::PositionOutOfBoard { player, pos })
+                }
+                if let Some(w) = positions.windows(2).find(|w| w[0] >= w[1]) {
+                    return Err(if w[0] == w[1] { BoardError::DuplicatedPosition { player, pos: w[0] } } else { BoardError::Unsorted { player } })
+                }
+            }
+            for &pos in &self.bases[player] {
+                if occupied[pos as usize] {
+                    return Err(BoardError::OverlappingBases { pos })
+                }
+                occupied[pos as usize] = true;
+            }
+        }
+
+        for player in 0..n_players {
+            let map = &self.distance_maps[player];
+            if map.len() != board_size {
+                return Err(BoardError::DistanceMapSize { player, size: map.len() })
+            }
+            if let Some(&pos) = self.targets[player].iter().find(|&&pos| map[pos as usize] == u64::MAX) {
+                return Err(BoardError::UnreachableTarget { player, pos })
+            }
+
+            let expected = tip_distance(&self.ajd_matrix, &self.bases[player], &self.targets[player]);
+            if let Some(pos) = (0..board_size).find(|&pos| map[pos] != expected[pos]) {
+                return Err(BoardError::DistanceMap { player, pos: pos as _, expected: expected[pos], actual: map[pos] })
+            }
+
+            // moves never leave the connected part of the board around the base, where every distance must be finite
+            let from_base = bfs_distance_from(&self.ajd_matrix, &self.bases[player]);
+            if let Some(pos) = (0..board_size).find(|&pos| from_base[pos] != u64::MAX && map[pos] == u64::MAX) {
+                return Err(BoardError::NoPathToTarget { player, pos: pos as _ })
+            }
+
+            let expected = self.targets[player].iter().map(|&pos| map[pos as usize]).sum();
+            if self.min_distances[player] != expected {
+                return Err(BoardError::MinDistance { player, expected, actual: self.min_distances[player] })
+            }
+
+            // otherwise a player could reach min_distance without filling the target
+            let farthest = self.targets[player].iter().map(|&pos| map[pos as usize]).max().unwrap();
+            if let Some(pos) = (0..board_size).find(|&pos| map[pos] <= farthest && !self.targets[player].contains(&(pos as Position))) {
+                return Err(BoardError::TargetNotMinimal { player, pos: pos as _ })
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn starting_pieces(&self) -> Vec<Position> {
-        let mut pieces = vec![];
-        pieces.extend_from_slice(self.p1_base);
-        pieces.extend_from_slice(self.p2_base);
-        pieces
-    }
-}
-
-pub static TINY_BOARD: Board = Board {
-    n_pieces: 3,
-    board_size: 37,
-    ajd_matrix: &[[1,2,255,255,255,255],[5,6,2,0,255,255],[6,7,255,255,0,1],[255,10,4,255,255,255],[10,11,5,255,255,3],[11,12,6,1,255,4],[12,13,7,2,1,5],[13,14,8,255,2,6],[14,15,9,255,255,7],[15,255,255,255,255,8],[255,16,11,4,3,255],[16,17,12,5,4,10],[17,18,13,6,5,11],[18,19,14,7,6,12],[19,20,15,8,7,13],[20,255,255,9,8,14],[21,22,17,11,10,255],[22,23,18,12,11,16],[23,24,19,13,12,17],[24,25,20,14,13,18],[25,26,255,15,14,19],[27,28,22,16,255,255],[28,29,23,17,16,21],[29,30,24,18,17,22],[30,31,25,19,18,23],[31,32,26,20,19,24],[32,33,255,255,20,25],[255,255,28,21,255,255],[255,255,29,22,21,27],[255,34,30,23,22,28],[34,35,31,24,23,29],[35,255,32,25,24,30],[255,255,33,26,25,31],[255,255,255,255,26,32],[255,36,35,30,29,255],[36,255,255,31,30,34],[255,255,255,35,34,255]],
-    p1_base: &[0, 1, 2],
-    p2_base: &[34, 35, 36],
-    p1_distance_map: &[8, 7, 7, 6, 6, 6, 6, 6, 6, 6, 5, 5, 5, 5, 5, 5, 4, 4, 4, 4, 4, 4, 3, 3, 3, 3, 4, 4, 3, 2, 2, 2, 3, 4, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 4, 3, 2, 2, 2, 3, 4, 4, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 5, 5, 5, 5, 5, 5, 6, 6, 6, 6, 6, 6, 6, 7, 7, 8],
-    min_distance: 2
-};
-
-pub static SMALL_BOARD: Board = Board {
-    n_pieces: 6,
-    board_size: 73,
-    ajd_matrix: &[[1,2,255,255,255,255],[3,4,2,0,255,255],[4,5,255,255,0,1],[9,10,4,1,255,255],[10,11,5,2,1,3],[11,12,255,255,2,4],[255,16,7,255,255,255],[16,17,8,255,255,6],[17,18,9,255,255,7],[18,19,10,3,255,8],[19,20,11,4,3,9],[20,21,12,5,4,10],[21,22,13,255,5,11],[22,23,14,255,255,12],[23,24,15,255,255,13],[24,255,255,255,255,14],[255,25,17,7,6,255],[25,26,18,8,7,16],[26,27,19,9,8,17],[27,28,20,10,9,18],[28,29,21,11,10,19],[29,30,22,12,11,20],[30,31,23,13,12,21],[31,32,24,14,13,22],[32,255,255,15,14,23],[255,33,26,17,16,255],[33,34,27,18,17,25],[34,35,28,19,18,26],[35,36,29,20,19,27],[36,37,30,21,20,28],[37,38,31,22,21,29],[38,39,32,23,22,30],[39,255,255,24,23,31],[40,41,34,26,25,255],[41,42,35,27,26,33],[42,43,36,28,27,34],[43,44,37,29,28,35],[44,45,38,30,29,36],[45,46,39,31,30,37],[46,47,255,32,31,38],[48,49,41,33,255,255],[49,50,42,34,33,40],[50,51,43,35,34,41],[51,52,44,36,35,42],[52,53,45,37,36,43],[53,54,46,38,37,44],[54,55,47,39,38,45],[55,56,255,255,39,46],[57,58,49,40,255,255],[58,59,50,41,40,48],[59,60,51,42,41,49],[60,61,52,43,42,50],[61,62,53,44,43,51],[62,63,54,45,44,52],[63,64,55,46,45,53],[64,65,56,47,46,54],[65,66,255,255,47,55],[255,255,58,48,255,255],[255,255,59,49,48,57],[255,255,60,50,49,58],[255,67,61,51,50,59],[67,68,62,52,51,60],[68,69,63,53,52,61],[69,255,64,54,53,62],[255,255,65,55,54,63],[255,255,66,56,55,64],[255,255,255,255,56,65],[255,70,68,61,60,255],[70,71,69,62,61,67],[71,255,255,63,62,68],[255,72,71,68,67,255],[72,255,255,69,68,70],[255,255,255,71,70,255]],
-    p1_base: &[0, 1, 2, 3, 4, 5],
-    p2_base: &[67, 68, 69, 70, 71, 72],
-    p1_distance_map: &[12, 11, 11, 10, 10, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 7, 7, 7, 7, 7, 7, 7, 7, 6, 6, 6, 6, 6, 6, 6, 6, 5, 5, 5, 5, 5, 5, 6, 6, 5, 4, 4, 4, 4, 4, 5, 6, 6, 5, 4, 3, 3, 3, 3, 4, 5, 6, 2, 2, 2, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 2, 2, 2, 6, 5, 4, 3, 3, 3, 3, 4, 5, 6, 6, 5, 4, 4, 4, 4, 4, 5, 6, 6, 5, 5, 5, 5, 5, 5, 6, 6, 6, 6, 6, 6, 6, 6, 7, 7, 7, 7, 7, 7, 7, 7, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 10, 10, 11, 11, 12],
-    min_distance: 8
-};
-
-pub static STANDARD_BOARD: Board = Board {
-    n_pieces: 10,
-    board_size: 121,
-    ajd_matrix: &[[1,2,255,255,255,255],[3,4,2,0,255,255],[4,5,255,255,0,1],[6,7,4,1,255,255],[7,8,5,2,1,3],[8,9,255,255,2,4],[14,15,7,3,255,255],[15,16,8,4,3,6],[16,17,9,5,4,7],[17,18,255,255,5,8],[255,23,11,255,255,255],[23,24,12,255,255,10],[24,25,13,255,255,11],[25,26,14,255,255,12],[26,27,15,6,255,13],[27,28,16,7,6,14],[28,29,17,8,7,15],[29,30,18,9,8,16],[30,31,19,255,9,17],[31,32,20,255,255,18],[32,33,21,255,255,19],[33,34,22,255,255,20],[34,255,255,255,255,21],[255,35,24,11,10,255],[35,36,25,12,11,23],[36,37,26,13,12,24],[37,38,27,14,13,25],[38,39,28,15,14,26],[39,40,29,16,15,27],[40,41,30,17,16,28],[41,42,31,18,17,29],[42,43,32,19,18,30],[43,44,33,20,19,31],[44,45,34,21,20,32],[45,255,255,22,21,33],[255,46,36,24,23,255],[46,47,37,25,24,35],[47,48,38,26,25,36],[48,49,39,27,26,37],[49,50,40,28,27,38],[50,51,41,29,28,39],[51,52,42,30,29,40],[52,53,43,31,30,41],[53,54,44,32,31,42],[54,55,45,33,32,43],[55,255,255,34,33,44],[255,56,47,36,35,255],[56,57,48,37,36,46],[57,58,49,38,37,47],[58,59,50,39,38,48],[59,60,51,40,39,49],[60,61,52,41,40,50],[61,62,53,42,41,51],[62,63,54,43,42,52],[63,64,55,44,43,53],[64,255,255,45,44,54],[65,66,57,47,46,255],[66,67,58,48,47,56],[67,68,59,49,48,57],[68,69,60,50,49,58],[69,70,61,51,50,59],[70,71,62,52,51,60],[71,72,63,53,52,61],[72,73,64,54,53,62],[73,74,255,55,54,63],[75,76,66,56,255,255],[76,77,67,57,56,65],[77,78,68,58,57,66],[78,79,69,59,58,67],[79,80,70,60,59,68],[80,81,71,61,60,69],[81,82,72,62,61,70],[82,83,73,63,62,71],[83,84,74,64,63,72],[84,85,255,255,64,73],[86,87,76,65,255,255],[87,88,77,66,65,75],[88,89,78,67,66,76],[89,90,79,68,67,77],[90,91,80,69,68,78],[91,92,81,70,69,79],[92,93,82,71,70,80],[93,94,83,72,71,81],[94,95,84,73,72,82],[95,96,85,74,73,83],[96,97,255,255,74,84],[98,99,87,75,255,255],[99,100,88,76,75,86],[100,101,89,77,76,87],[101,102,90,78,77,88],[102,103,91,79,78,89],[103,104,92,80,79,90],[104,105,93,81,80,91],[105,106,94,82,81,92],[106,107,95,83,82,93],[107,108,96,84,83,94],[108,109,97,85,84,95],[109,110,255,255,85,96],[255,255,99,86,255,255],[255,255,100,87,86,98],[255,255,101,88,87,99],[255,255,102,89,88,100],[255,111,103,90,89,101],[111,112,104,91,90,102],[112,113,105,92,91,103],[113,114,106,93,92,104],[114,255,107,94,93,105],[255,255,108,95,94,106],[255,255,109,96,95,107],[255,255,110,97,96,108],[255,255,255,255,97,109],[255,115,112,103,102,255],[115,116,113,104,103,111],[116,117,114,105,104,112],[117,255,255,106,105,113],[255,118,116,112,111,255],[118,119,117,113,112,115],[119,255,255,114,113,116],[255,120,119,116,115,255],[120,255,255,117,116,118],[255,255,255,119,118,255]],
-    p1_base: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
-    p2_base: &[111, 112, 113, 114, 115, 116, 117, 118, 119, 120],
-    p1_distance_map: &[16, 15, 15, 14, 14, 14, 13, 13, 13, 13, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 8, 7, 6, 5, 5, 5, 5, 5, 5, 6, 7, 8, 8, 7, 6, 5, 4, 4, 4, 4, 4, 5, 6, 7, 8, 3, 3, 3, 3, 2, 2, 2, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 2, 2, 2, 3, 3, 3, 3, 8, 7, 6, 5, 4, 4, 4, 4, 4, 5, 6, 7, 8, 8, 7, 6, 5, 5, 5, 5, 5, 5, 6, 7, 8, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 13, 13, 13, 13, 14, 14, 14, 15, 15, 16],
-    min_distance: 20
-};
-
-pub static LARGE_BOARD: Board = Board {
-    n_pieces: 15,
-    board_size: 181,
-    ajd_matrix: &[[1,2,255,255,255,255],[3,4,2,0,255,255],[4,5,255,255,0,1],[6,7,4,1,255,255],[7,8,5,2,1,3],[8,9,255,255,2,4],[10,11,7,3,255,255],[11,12,8,4,3,6],[12,13,9,5,4,7],[13,14,255,255,5,8],[20,21,11,6,255,255],[21,22,12,7,6,10],[22,23,13,8,7,11],[23,24,14,9,8,12],[24,25,255,255,9,13],[255,31,16,255,255,255],[31,32,17,255,255,15],[32,33,18,255,255,16],[33,34,19,255,255,17],[34,35,20,255,255,18],[35,36,21,10,255,19],[36,37,22,11,10,20],[37,38,23,12,11,21],[38,39,24,13,12,22],[39,40,25,14,13,23],[40,41,26,255,14,24],[41,42,27,255,255,25],[42,43,28,255,255,26],[43,44,29,255,255,27],[44,45,30,255,255,28],[45,255,255,255,255,29],[255,46,32,16,15,255],[46,47,33,17,16,31],[47,48,34,18,17,32],[48,49,35,19,18,33],[49,50,36,20,19,34],[50,51,37,21,20,35],[51,52,38,22,21,36],[52,53,39,23,22,37],[53,54,40,24,23,38],[54,55,41,25,24,39],[55,56,42,26,25,40],[56,57,43,27,26,41],[57,58,44,28,27,42],[58,59,45,29,28,43],[59,255,255,30,29,44],[255,60,47,32,31,255],[60,61,48,33,32,46],[61,62,49,34,33,47],[62,63,50,35,34,48],[63,64,51,36,35,49],[64,65,52,37,36,50],[65,66,53,38,37,51],[66,67,54,39,38,52],[67,68,55,40,39,53],[68,69,56,41,40,54],[69,70,57,42,41,55],[70,71,58,43,42,56],[71,72,59,44,43,57],[72,255,255,45,44,58],[255,73,61,47,46,255],[73,74,62,48,47,60],[74,75,63,49,48,61],[75,76,64,50,49,62],[76,77,65,51,50,63],[77,78,66,52,51,64],[78,79,67,53,52,65],[79,80,68,54,53,66],[80,81,69,55,54,67],[81,82,70,56,55,68],[82,83,71,57,56,69],[83,84,72,58,57,70],[84,255,255,59,58,71],[255,85,74,61,60,255],[85,86,75,62,61,73],[86,87,76,63,62,74],[87,88,77,64,63,75],[88,89,78,65,64,76],[89,90,79,66,65,77],[90,91,80,67,66,78],[91,92,81,68,67,79],[92,93,82,69,68,80],[93,94,83,70,69,81],[94,95,84,71,70,82],[95,255,255,72,71,83],[96,97,86,74,73,255],[97,98,87,75,74,85],[98,99,88,76,75,86],[99,100,89,77,76,87],[100,101,90,78,77,88],[101,102,91,79,78,89],[102,103,92,80,79,90],[103,104,93,81,80,91],[104,105,94,82,81,92],[105,106,95,83,82,93],[106,107,255,84,83,94],[108,109,97,85,255,255],[109,110,98,86,85,96],[110,111,99,87,86,97],[111,112,100,88,87,98],[112,113,101,89,88,99],[113,114,102,90,89,100],[114,115,103,91,90,101],[115,116,104,92,91,102],[116,117,105,93,92,103],[117,118,106,94,93,104],[118,119,107,95,94,105],[119,120,255,255,95,106],[121,122,109,96,255,255],[122,123,110,97,96,108],[123,124,111,98,97,109],[124,125,112,99,98,110],[125,126,113,100,99,111],[126,127,114,101,100,112],[127,128,115,102,101,113],[128,129,116,103,102,114],[129,130,117,104,103,115],[130,131,118,105,104,116],[131,132,119,106,105,117],[132,133,120,107,106,118],[133,134,255,255,107,119],[135,136,122,108,255,255],[136,137,123,109,108,121],[137,138,124,110,109,122],[138,139,125,111,110,123],[139,140,126,112,111,124],[140,141,127,113,112,125],[141,142,128,114,113,126],[142,143,129,115,114,127],[143,144,130,116,115,128],[144,145,131,117,116,129],[145,146,132,118,117,130],[146,147,133,119,118,131],[147,148,134,120,119,132],[148,149,255,255,120,133],[150,151,136,121,255,255],[151,152,137,122,121,135],[152,153,138,123,122,136],[153,154,139,124,123,137],[154,155,140,125,124,138],[155,156,141,126,125,139],[156,157,142,127,126,140],[157,158,143,128,127,141],[158,159,144,129,128,142],[159,160,145,130,129,143],[160,161,146,131,130,144],[161,162,147,132,131,145],[162,163,148,133,132,146],[163,164,149,134,133,147],[164,165,255,255,134,148],[255,255,151,135,255,255],[255,255,152,136,135,150],[255,255,153,137,136,151],[255,255,154,138,137,152],[255,255,155,139,138,153],[255,166,156,140,139,154],[166,167,157,141,140,155],[167,168,158,142,141,156],[168,169,159,143,142,157],[169,170,160,144,143,158],[170,255,161,145,144,159],[255,255,162,146,145,160],[255,255,163,147,146,161],[255,255,164,148,147,162],[255,255,165,149,148,163],[255,255,255,255,149,164],[255,171,167,156,155,255],[171,172,168,157,156,166],[172,173,169,158,157,167],[173,174,170,159,158,168],[174,255,255,160,159,169],[255,175,172,167,166,255],[175,176,173,168,167,171],[176,177,174,169,168,172],[177,255,255,170,169,173],[255,178,176,172,171,255],[178,179,177,173,172,175],[179,255,255,174,173,176],[255,180,179,176,175,255],[180,255,255,177,176,178],[255,255,255,179,178,255]],
-    p1_base: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14],
-    p2_base: &[166, 167, 168, 169, 170, 171, 172, 173, 174, 175, 176, 177, 178, 179, 180],
-    p1_distance_map: &[20, 19, 19, 18, 18, 18, 17, 17, 17, 17, 16, 16, 16, 16, 16, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 10, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 10, 10, 9, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10, 10, 9, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 9, 10, 10, 9, 8, 7, 6, 5, 5, 5, 5, 5, 5, 6, 7, 8, 9, 10, 4, 4, 4, 4, 4, 3, 3, 3, 3, 2, 2, 2, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 4, 10, 9, 8, 7, 6, 5, 5, 5, 5, 5, 5, 6, 7, 8, 9, 10, 10, 9, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 9, 10, 10, 9, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10, 10, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 10, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 16, 16, 16, 16, 16, 17, 17, 17, 17, 18, 18, 18, 19, 19, 20],
-    min_distance: 40
-};
-
-pub static HUGE_BOARD: Board = Board {
-    n_pieces: 21,
-    board_size: 253,
-    ajd_matrix: &[[1,2,255,255,255,255],[3,4,2,0,255,255],[4,5,255,255,0,1],[6,7,4,1,255,255],[7,8,5,2,1,3],[8,9,255,255,2,4],[10,11,7,3,255,255],[11,12,8,4,3,6],[12,13,9,5,4,7],[13,14,255,255,5,8],[15,16,11,6,255,255],[16,17,12,7,6,10],[17,18,13,8,7,11],[18,19,14,9,8,12],[19,20,255,255,9,13],[27,28,16,10,255,255],[28,29,17,11,10,15],[29,30,18,12,11,16],[30,31,19,13,12,17],[31,32,20,14,13,18],[32,33,255,255,14,19],[255,40,22,255,255,255],[40,41,23,255,255,21],[41,42,24,255,255,22],[42,43,25,255,255,23],[43,44,26,255,255,24],[44,45,27,255,255,25],[45,46,28,15,255,26],[46,47,29,16,15,27],[47,48,30,17,16,28],[48,49,31,18,17,29],[49,50,32,19,18,30],[50,51,33,20,19,31],[51,52,34,255,20,32],[52,53,35,255,255,33],[53,54,36,255,255,34],[54,55,37,255,255,35],[55,56,38,255,255,36],[56,57,39,255,255,37],[57,255,255,255,255,38],[255,58,41,22,21,255],[58,59,42,23,22,40],[59,60,43,24,23,41],[60,61,44,25,24,42],[61,62,45,26,25,43],[62,63,46,27,26,44],[63,64,47,28,27,45],[64,65,48,29,28,46],[65,66,49,30,29,47],[66,67,50,31,30,48],[67,68,51,32,31,49],[68,69,52,33,32,50],[69,70,53,34,33,51],[70,71,54,35,34,52],[71,72,55,36,35,53],[72,73,56,37,36,54],[73,74,57,38,37,55],[74,255,255,39,38,56],[255,75,59,41,40,255],[75,76,60,42,41,58],[76,77,61,43,42,59],[77,78,62,44,43,60],[78,79,63,45,44,61],[79,80,64,46,45,62],[80,81,65,47,46,63],[81,82,66,48,47,64],[82,83,67,49,48,65],[83,84,68,50,49,66],[84,85,69,51,50,67],[85,86,70,52,51,68],[86,87,71,53,52,69],[87,88,72,54,53,70],[88,89,73,55,54,71],[89,90,74,56,55,72],[90,255,255,57,56,73],[255,91,76,59,58,255],[91,92,77,60,59,75],[92,93,78,61,60,76],[93,94,79,62,61,77],[94,95,80,63,62,78],[95,96,81,64,63,79],[96,97,82,65,64,80],[97,98,83,66,65,81],[98,99,84,67,66,82],[99,100,85,68,67,83],[100,101,86,69,68,84],[101,102,87,70,69,85],[102,103,88,71,70,86],[103,104,89,72,71,87],[104,105,90,73,72,88],[105,255,255,74,73,89],[255,106,92,76,75,255],[106,107,93,77,76,91],[107,108,94,78,77,92],[108,109,95,79,78,93],[109,110,96,80,79,94],[110,111,97,81,80,95],[111,112,98,82,81,96],[112,113,99,83,82,97],[113,114,100,84,83,98],[114,115,101,85,84,99],[115,116,102,86,85,100],[116,117,103,87,86,101],[117,118,104,88,87,102],[118,119,105,89,88,103],[119,255,255,90,89,104],[255,120,107,92,91,255],[120,121,108,93,92,106],[121,122,109,94,93,107],[122,123,110,95,94,108],[123,124,111,96,95,109],[124,125,112,97,96,110],[125,126,113,98,97,111],[126,127,114,99,98,112],[127,128,115,100,99,113],[128,129,116,101,100,114],[129,130,117,102,101,115],[130,131,118,103,102,116],[131,132,119,104,103,117],[132,255,255,105,104,118],[133,134,121,107,106,255],[134,135,122,108,107,120],[135,136,123,109,108,121],[136,137,124,110,109,122],[137,138,125,111,110,123],[138,139,126,112,111,124],[139,140,127,113,112,125],[140,141,128,114,113,126],[141,142,129,115,114,127],[142,143,130,116,115,128],[143,144,131,117,116,129],[144,145,132,118,117,130],[145,146,255,119,118,131],[147,148,134,120,255,255],[148,149,135,121,120,133],[149,150,136,122,121,134],[150,151,137,123,122,135],[151,152,138,124,123,136],[152,153,139,125,124,137],[153,154,140,126,125,138],[154,155,141,127,126,139],[155,156,142,128,127,140],[156,157,143,129,128,141],[157,158,144,130,129,142],[158,159,145,131,130,143],[159,160,146,132,131,144],[160,161,255,255,132,145],[162,163,148,133,255,255],[163,164,149,134,133,147],[164,165,150,135,134,148],[165,166,151,136,135,149],[166,167,152,137,136,150],[167,168,153,138,137,151],[168,169,154,139,138,152],[169,170,155,140,139,153],[170,171,156,141,140,154],[171,172,157,142,141,155],[172,173,158,143,142,156],[173,174,159,144,143,157],[174,175,160,145,144,158],[175,176,161,146,145,159],[176,177,255,255,146,160],[178,179,163,147,255,255],[179,180,164,148,147,162],[180,181,165,149,148,163],[181,182,166,150,149,164],[182,183,167,151,150,165],[183,184,168,152,151,166],[184,185,169,153,152,167],[185,186,170,154,153,168],[186,187,171,155,154,169],[187,188,172,156,155,170],[188,189,173,157,156,171],[189,190,174,158,157,172],[190,191,175,159,158,173],[191,192,176,160,159,174],[192,193,177,161,160,175],[193,194,255,255,161,176],[195,196,179,162,255,255],[196,197,180,163,162,178],[197,198,181,164,163,179],[198,199,182,165,164,180],[199,200,183,166,165,181],[200,201,184,167,166,182],[201,202,185,168,167,183],[202,203,186,169,168,184],[203,204,187,170,169,185],[204,205,188,171,170,186],[205,206,189,172,171,187],[206,207,190,173,172,188],[207,208,191,174,173,189],[208,209,192,175,174,190],[209,210,193,176,175,191],[210,211,194,177,176,192],[211,212,255,255,177,193],[213,214,196,178,255,255],[214,215,197,179,178,195],[215,216,198,180,179,196],[216,217,199,181,180,197],[217,218,200,182,181,198],[218,219,201,183,182,199],[219,220,202,184,183,200],[220,221,203,185,184,201],[221,222,204,186,185,202],[222,223,205,187,186,203],[223,224,206,188,187,204],[224,225,207,189,188,205],[225,226,208,190,189,206],[226,227,209,191,190,207],[227,228,210,192,191,208],[228,229,211,193,192,209],[229,230,212,194,193,210],[230,231,255,255,194,211],[255,255,214,195,255,255],[255,255,215,196,195,213],[255,255,216,197,196,214],[255,255,217,198,197,215],[255,255,218,199,198,216],[255,255,219,200,199,217],[255,232,220,201,200,218],[232,233,221,202,201,219],[233,234,222,203,202,220],[234,235,223,204,203,221],[235,236,224,205,204,222],[236,237,225,206,205,223],[237,255,226,207,206,224],[255,255,227,208,207,225],[255,255,228,209,208,226],[255,255,229,210,209,227],[255,255,230,211,210,228],[255,255,231,212,211,229],[255,255,255,255,212,230],[255,238,233,220,219,255],[238,239,234,221,220,232],[239,240,235,222,221,233],[240,241,236,223,222,234],[241,242,237,224,223,235],[242,255,255,225,224,236],[255,243,239,233,232,255],[243,244,240,234,233,238],[244,245,241,235,234,239],[245,246,242,236,235,240],[246,255,255,237,236,241],[255,247,244,239,238,255],[247,248,245,240,239,243],[248,249,246,241,240,244],[249,255,255,242,241,245],[255,250,248,244,243,255],[250,251,249,245,244,247],[251,255,255,246,245,248],[255,252,251,248,247,255],[252,255,255,249,248,250],[255,255,255,251,250,255]],
-    p1_base: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20],
-    p2_base: &[232, 233, 234, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247, 248, 249, 250, 251, 252],
-    p1_distance_map: &[24, 23, 23, 22, 22, 22, 21, 21, 21, 21, 20, 20, 20, 20, 20, 19, 19, 19, 19, 19, 19, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 12, 12, 11, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 11, 12, 12, 11, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 11, 12, 12, 11, 10, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 10, 11, 12, 12, 11, 10, 9, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10, 11, 12, 12, 11, 10, 9, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 9, 10, 11, 12, 5, 5, 5, 5, 5, 5, 4, 4, 4, 4, 4, 3, 3, 3, 3, 2, 2, 2, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 4, 5, 5, 5, 5, 5, 5, 12, 11, 10, 9, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 9, 10, 11, 12, 12, 11, 10, 9, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10, 11, 12, 12, 11, 10, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 10, 11, 12, 12, 11, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 11, 12, 12, 11, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 11, 12, 12, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 19, 19, 19, 19, 19, 19, 20, 20, 20, 20, 20, 21, 21, 21, 21, 22, 22, 22, 23, 23, 24],
-    min_distance: 70
-};
-
-pub static TINY_PLUS_BOARD: Board = Board {
-    n_pieces: 6,
-    board_size: 37,
-    ajd_matrix: &[[1,2,255,255,255,255],[5,6,2,0,255,255],[6,7,255,255,0,1],[255,10,4,255,255,255],[10,11,5,255,255,3],[11,12,6,1,255,4],[12,13,7,2,1,5],[13,14,8,255,2,6],[14,15,9,255,255,7],[15,255,255,255,255,8],[255,16,11,4,3,255],[16,17,12,5,4,10],[17,18,13,6,5,11],[18,19,14,7,6,12],[19,20,15,8,7,13],[20,255,255,9,8,14],[21,22,17,11,10,255],[22,23,18,12,11,16],[23,24,19,13,12,17],[24,25,20,14,13,18],[25,26,255,15,14,19],[27,28,22,16,255,255],[28,29,23,17,16,21],[29,30,24,18,17,22],[30,31,25,19,18,23],[31,32,26,20,19,24],[32,33,255,255,20,25],[255,255,28,21,255,255],[255,255,29,22,21,27],[255,34,30,23,22,28],[34,35,31,24,23,29],[35,255,32,25,24,30],[255,255,33,26,25,31],[255,255,255,255,26,32],[255,36,35,30,29,255],[36,255,255,31,30,34],[255,255,255,35,34,255]],
-    p1_base: &[0, 1, 2, 5, 6, 7],
-    p2_base: &[29, 30, 31, 34, 35, 36],
-    p1_distance_map: &[8, 7, 7, 6, 6, 6, 6, 6, 6, 6, 5, 5, 5, 5, 5, 5, 4, 4, 4, 4, 4, 4, 3, 3, 3, 3, 4, 4, 3, 2, 2, 2, 3, 4, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 4, 3, 2, 2, 2, 3, 4, 4, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 5, 5, 5, 5, 5, 5, 6, 6, 6, 6, 6, 6, 6, 7, 7, 8],
-    min_distance: 8
-};
-
-pub static SMALL_PLUS_BOARD: Board = Board {
-    n_pieces: 10,
-    board_size: 73,
-    ajd_matrix: &[[1,2,255,255,255,255],[3,4,2,0,255,255],[4,5,255,255,0,1],[9,10,4,1,255,255],[10,11,5,2,1,3],[11,12,255,255,2,4],[255,16,7,255,255,255],[16,17,8,255,255,6],[17,18,9,255,255,7],[18,19,10,3,255,8],[19,20,11,4,3,9],[20,21,12,5,4,10],[21,22,13,255,5,11],[22,23,14,255,255,12],[23,24,15,255,255,13],[24,255,255,255,255,14],[255,25,17,7,6,255],[25,26,18,8,7,16],[26,27,19,9,8,17],[27,28,20,10,9,18],[28,29,21,11,10,19],[29,30,22,12,11,20],[30,31,23,13,12,21],[31,32,24,14,13,22],[32,255,255,15,14,23],[255,33,26,17,16,255],[33,34,27,18,17,25],[34,35,28,19,18,26],[35,36,29,20,19,27],[36,37,30,21,20,28],[37,38,31,22,21,29],[38,39,32,23,22,30],[39,255,255,24,23,31],[40,41,34,26,25,255],[41,42,35,27,26,33],[42,43,36,28,27,34],[43,44,37,29,28,35],[44,45,38,30,29,36],[45,46,39,31,30,37],[46,47,255,32,31,38],[48,49,41,33,255,255],[49,50,42,34,33,40],[50,51,43,35,34,41],[51,52,44,36,35,42],[52,53,45,37,36,43],[53,54,46,38,37,44],[54,55,47,39,38,45],[55,56,255,255,39,46],[57,58,49,40,255,255],[58,59,50,41,40,48],[59,60,51,42,41,49],[60,61,52,43,42,50],[61,62,53,44,43,51],[62,63,54,45,44,52],[63,64,55,46,45,53],[64,65,56,47,46,54],[65,66,255,255,47,55],[255,255,58,48,255,255],[255,255,59,49,48,57],[255,255,60,50,49,58],[255,67,61,51,50,59],[67,68,62,52,51,60],[68,69,63,53,52,61],[69,255,64,54,53,62],[255,255,65,55,54,63],[255,255,66,56,55,64],[255,255,255,255,56,65],[255,70,68,61,60,255],[70,71,69,62,61,67],[71,255,255,63,62,68],[255,72,71,68,67,255],[72,255,255,69,68,70],[255,255,255,71,70,255]],
-    p1_base: &[0, 1, 2, 3, 4, 5, 9, 10, 11, 12],
-    p2_base: &[60, 61, 62, 63, 67, 68, 69, 70, 71, 72],
-    p1_distance_map: &[12, 11, 11, 10, 10, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 7, 7, 7, 7, 7, 7, 7, 7, 6, 6, 6, 6, 6, 6, 6, 6, 5, 5, 5, 5, 5, 5, 6, 6, 5, 4, 4, 4, 4, 4, 5, 6, 6, 5, 4, 3, 3, 3, 3, 4, 5, 6, 2, 2, 2, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 2, 2, 2, 6, 5, 4, 3, 3, 3, 3, 4, 5, 6, 6, 5, 4, 4, 4, 4, 4, 5, 6, 6, 5, 5, 5, 5, 5, 5, 6, 6, 6, 6, 6, 6, 6, 6, 7, 7, 7, 7, 7, 7, 7, 7, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 10, 10, 11, 11, 12],
-    min_distance: 20
-};
-
-pub static STANDARD_PLUS_BOARD: Board = Board {
-    n_pieces: 15,
-    board_size: 121,
-    ajd_matrix: &[[1,2,255,255,255,255],[3,4,2,0,255,255],[4,5,255,255,0,1],[6,7,4,1,255,255],[7,8,5,2,1,3],[8,9,255,255,2,4],[14,15,7,3,255,255],[15,16,8,4,3,6],[16,17,9,5,4,7],[17,18,255,255,5,8],[255,23,11,255,255,255],[23,24,12,255,255,10],[24,25,13,255,255,11],[25,26,14,255,255,12],[26,27,15,6,255,13],[27,28,16,7,6,14],[28,29,17,8,7,15],[29,30,18,9,8,16],[30,31,19,255,9,17],[31,32,20,255,255,18],[32,33,21,255,255,19],[33,34,22,255,255,20],[34,255,255,255,255,21],[255,35,24,11,10,255],[35,36,25,12,11,23],[36,37,26,13,12,24],[37,38,27,14,13,25],[38,39,28,15,14,26],[39,40,29,16,15,27],[40,41,30,17,16,28],[41,42,31,18,17,29],[42,43,32,19,18,30],[43,44,33,20,19,31],[44,45,34,21,20,32],[45,255,255,22,21,33],[255,46,36,24,23,255],[46,47,37,25,24,35],[47,48,38,26,25,36],[48,49,39,27,26,37],[49,50,40,28,27,38],[50,51,41,29,28,39],[51,52,42,30,29,40],[52,53,43,31,30,41],[53,54,44,32,31,42],[54,55,45,33,32,43],[55,255,255,34,33,44],[255,56,47,36,35,255],[56,57,48,37,36,46],[57,58,49,38,37,47],[58,59,50,39,38,48],[59,60,51,40,39,49],[60,61,52,41,40,50],[61,62,53,42,41,51],[62,63,54,43,42,52],[63,64,55,44,43,53],[64,255,255,45,44,54],[65,66,57,47,46,255],[66,67,58,48,47,56],[67,68,59,49,48,57],[68,69,60,50,49,58],[69,70,61,51,50,59],[70,71,62,52,51,60],[71,72,63,53,52,61],[72,73,64,54,53,62],[73,74,255,55,54,63],[75,76,66,56,255,255],[76,77,67,57,56,65],[77,78,68,58,57,66],[78,79,69,59,58,67],[79,80,70,60,59,68],[80,81,71,61,60,69],[81,82,72,62,61,70],[82,83,73,63,62,71],[83,84,74,64,63,72],[84,85,255,255,64,73],[86,87,76,65,255,255],[87,88,77,66,65,75],[88,89,78,67,66,76],[89,90,79,68,67,77],[90,91,80,69,68,78],[91,92,81,70,69,79],[92,93,82,71,70,80],[93,94,83,72,71,81],[94,95,84,73,72,82],[95,96,85,74,73,83],[96,97,255,255,74,84],[98,99,87,75,255,255],[99,100,88,76,75,86],[100,101,89,77,76,87],[101,102,90,78,77,88],[102,103,91,79,78,89],[103,104,92,80,79,90],[104,105,93,81,80,91],[105,106,94,82,81,92],[106,107,95,83,82,93],[107,108,96,84,83,94],[108,109,97,85,84,95],[109,110,255,255,85,96],[255,255,99,86,255,255],[255,255,100,87,86,98],[255,255,101,88,87,99],[255,255,102,89,88,100],[255,111,103,90,89,101],[111,112,104,91,90,102],[112,113,105,92,91,103],[113,114,106,93,92,104],[114,255,107,94,93,105],[255,255,108,95,94,106],[255,255,109,96,95,107],[255,255,110,97,96,108],[255,255,255,255,97,109],[255,115,112,103,102,255],[115,116,113,104,103,111],[116,117,114,105,104,112],[117,255,255,106,105,113],[255,118,116,112,111,255],[118,119,117,113,112,115],[119,255,255,114,113,116],[255,120,119,116,115,255],[120,255,255,117,116,118],[255,255,255,119,118,255]],
-    p1_base: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 14, 15, 16, 17, 18],
-    p2_base: &[102, 103, 104, 105, 106, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120],
-    p1_distance_map: &[16, 15, 15, 14, 14, 14, 13, 13, 13, 13, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 8, 7, 6, 5, 5, 5, 5, 5, 5, 6, 7, 8, 8, 7, 6, 5, 4, 4, 4, 4, 4, 5, 6, 7, 8, 3, 3, 3, 3, 2, 2, 2, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 2, 2, 2, 3, 3, 3, 3, 8, 7, 6, 5, 4, 4, 4, 4, 4, 5, 6, 7, 8, 8, 7, 6, 5, 5, 5, 5, 5, 5, 6, 7, 8, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 13, 13, 13, 13, 14, 14, 14, 15, 15, 16],
-    min_distance: 40
-};
-
-pub static LARGE_PLUS_BOARD: Board = Board {
-    n_pieces: 21,
-    board_size: 181,
-    ajd_matrix: &[[1,2,255,255,255,255],[3,4,2,0,255,255],[4,5,255,255,0,1],[6,7,4,1,255,255],[7,8,5,2,1,3],[8,9,255,255,2,4],[10,11,7,3,255,255],[11,12,8,4,3,6],[12,13,9,5,4,7],[13,14,255,255,5,8],[20,21,11,6,255,255],[21,22,12,7,6,10],[22,23,13,8,7,11],[23,24,14,9,8,12],[24,25,255,255,9,13],[255,31,16,255,255,255],[31,32,17,255,255,15],[32,33,18,255,255,16],[33,34,19,255,255,17],[34,35,20,255,255,18],[35,36,21,10,255,19],[36,37,22,11,10,20],[37,38,23,12,11,21],[38,39,24,13,12,22],[39,40,25,14,13,23],[40,41,26,255,14,24],[41,42,27,255,255,25],[42,43,28,255,255,26],[43,44,29,255,255,27],[44,45,30,255,255,28],[45,255,255,255,255,29],[255,46,32,16,15,255],[46,47,33,17,16,31],[47,48,34,18,17,32],[48,49,35,19,18,33],[49,50,36,20,19,34],[50,51,37,21,20,35],[51,52,38,22,21,36],[52,53,39,23,22,37],[53,54,40,24,23,38],[54,55,41,25,24,39],[55,56,42,26,25,40],[56,57,43,27,26,41],[57,58,44,28,27,42],[58,59,45,29,28,43],[59,255,255,30,29,44],[255,60,47,32,31,255],[60,61,48,33,32,46],[61,62,49,34,33,47],[62,63,50,35,34,48],[63,64,51,36,35,49],[64,65,52,37,36,50],[65,66,53,38,37,51],[66,67,54,39,38,52],[67,68,55,40,39,53],[68,69,56,41,40,54],[69,70,57,42,41,55],[70,71,58,43,42,56],[71,72,59,44,43,57],[72,255,255,45,44,58],[255,73,61,47,46,255],[73,74,62,48,47,60],[74,75,63,49,48,61],[75,76,64,50,49,62],[76,77,65,51,50,63],[77,78,66,52,51,64],[78,79,67,53,52,65],[79,80,68,54,53,66],[80,81,69,55,54,67],[81,82,70,56,55,68],[82,83,71,57,56,69],[83,84,72,58,57,70],[84,255,255,59,58,71],[255,85,74,61,60,255],[85,86,75,62,61,73],[86,87,76,63,62,74],[87,88,77,64,63,75],[88,89,78,65,64,76],[89,90,79,66,65,77],[90,91,80,67,66,78],[91,92,81,68,67,79],[92,93,82,69,68,80],[93,94,83,70,69,81],[94,95,84,71,70,82],[95,255,255,72,71,83],[96,97,86,74,73,255],[97,98,87,75,74,85],[98,99,88,76,75,86],[99,100,89,77,76,87],[100,101,90,78,77,88],[101,102,91,79,78,89],[102,103,92,80,79,90],[103,104,93,81,80,91],[104,105,94,82,81,92],[105,106,95,83,82,93],[106,107,255,84,83,94],[108,109,97,85,255,255],[109,110,98,86,85,96],[110,111,99,87,86,97],[111,112,100,88,87,98],[112,113,101,89,88,99],[113,114,102,90,89,100],[114,115,103,91,90,101],[115,116,104,92,91,102],[116,117,105,93,92,103],[117,118,106,94,93,104],[118,119,107,95,94,105],[119,120,255,255,95,106],[121,122,109,96,255,255],[122,123,110,97,96,108],[123,124,111,98,97,109],[124,125,112,99,98,110],[125,126,113,100,99,111],[126,127,114,101,100,112],[127,128,115,102,101,113],[128,129,116,103,102,114],[129,130,117,104,103,115],[130,131,118,105,104,116],[131,132,119,106,105,117],[132,133,120,107,106,118],[133,134,255,255,107,119],[135,136,122,108,255,255],[136,137,123,109,108,121],[137,138,124,110,109,122],[138,139,125,111,110,123],[139,140,126,112,111,124],[140,141,127,113,112,125],[141,142,128,114,113,126],[142,143,129,115,114,127],[143,144,130,116,115,128],[144,145,131,117,116,129],[145,146,132,118,117,130],[146,147,133,119,118,131],[147,148,134,120,119,132],[148,149,255,255,120,133],[150,151,136,121,255,255],[151,152,137,122,121,135],[152,153,138,123,122,136],[153,154,139,124,123,137],[154,155,140,125,124,138],[155,156,141,126,125,139],[156,157,142,127,126,140],[157,158,143,128,127,141],[158,159,144,129,128,142],[159,160,145,130,129,143],[160,161,146,131,130,144],[161,162,147,132,131,145],[162,163,148,133,132,146],[163,164,149,134,133,147],[164,165,255,255,134,148],[255,255,151,135,255,255],[255,255,152,136,135,150],[255,255,153,137,136,151],[255,255,154,138,137,152],[255,255,155,139,138,153],[255,166,156,140,139,154],[166,167,157,141,140,155],[167,168,158,142,141,156],[168,169,159,143,142,157],[169,170,160,144,143,158],[170,255,161,145,144,159],[255,255,162,146,145,160],[255,255,163,147,146,161],[255,255,164,148,147,162],[255,255,165,149,148,163],[255,255,255,255,149,164],[255,171,167,156,155,255],[171,172,168,157,156,166],[172,173,169,158,157,167],[173,174,170,159,158,168],[174,255,255,160,159,169],[255,175,172,167,166,255],[175,176,173,168,167,171],[176,177,174,169,168,172],[177,255,255,170,169,173],[255,178,176,172,171,255],[178,179,177,173,172,175],[179,255,255,174,173,176],[255,180,179,176,175,255],[180,255,255,177,176,178],[255,255,255,179,178,255]],
-    p1_base: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 20, 21, 22, 23, 24, 25],
-    p2_base: &[155, 156, 157, 158, 159, 160, 166, 167, 168, 169, 170, 171, 172, 173, 174, 175, 176, 177, 178, 179, 180],
-    p1_distance_map: &[20, 19, 19, 18, 18, 18, 17, 17, 17, 17, 16, 16, 16, 16, 16, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 10, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 10, 10, 9, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10, 10, 9, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 9, 10, 10, 9, 8, 7, 6, 5, 5, 5, 5, 5, 5, 6, 7, 8, 9, 10, 4, 4, 4, 4, 4, 3, 3, 3, 3, 2, 2, 2, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 4, 10, 9, 8, 7, 6, 5, 5, 5, 5, 5, 5, 6, 7, 8, 9, 10, 10, 9, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 9, 10, 10, 9, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10, 10, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 10, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 16, 16, 16, 16, 16, 17, 17, 17, 17, 18, 18, 18, 19, 19, 20],
-    min_distance: 70
-};
-
-pub static HUGE_PLUS_BOARD: Board = Board {
-    n_pieces: 28,
-    board_size: 253,
-    ajd_matrix: &[[1,2,255,255,255,255],[3,4,2,0,255,255],[4,5,255,255,0,1],[6,7,4,1,255,255],[7,8,5,2,1,3],[8,9,255,255,2,4],[10,11,7,3,255,255],[11,12,8,4,3,6],[12,13,9,5,4,7],[13,14,255,255,5,8],[15,16,11,6,255,255],[16,17,12,7,6,10],[17,18,13,8,7,11],[18,19,14,9,8,12],[19,20,255,255,9,13],[27,28,16,10,255,255],[28,29,17,11,10,15],[29,30,18,12,11,16],[30,31,19,13,12,17],[31,32,20,14,13,18],[32,33,255,255,14,19],[255,40,22,255,255,255],[40,41,23,255,255,21],[41,42,24,255,255,22],[42,43,25,255,255,23],[43,44,26,255,255,24],[44,45,27,255,255,25],[45,46,28,15,255,26],[46,47,29,16,15,27],[47,48,30,17,16,28],[48,49,31,18,17,29],[49,50,32,19,18,30],[50,51,33,20,19,31],[51,52,34,255,20,32],[52,53,35,255,255,33],[53,54,36,255,255,34],[54,55,37,255,255,35],[55,56,38,255,255,36],[56,57,39,255,255,37],[57,255,255,255,255,38],[255,58,41,22,21,255],[58,59,42,23,22,40],[59,60,43,24,23,41],[60,61,44,25,24,42],[61,62,45,26,25,43],[62,63,46,27,26,44],[63,64,47,28,27,45],[64,65,48,29,28,46],[65,66,49,30,29,47],[66,67,50,31,30,48],[67,68,51,32,31,49],[68,69,52,33,32,50],[69,70,53,34,33,51],[70,71,54,35,34,52],[71,72,55,36,35,53],[72,73,56,37,36,54],[73,74,57,38,37,55],[74,255,255,39,38,56],[255,75,59,41,40,255],[75,76,60,42,41,58],[76,77,61,43,42,59],[77,78,62,44,43,60],[78,79,63,45,44,61],[79,80,64,46,45,62],[80,81,65,47,46,63],[81,82,66,48,47,64],[82,83,67,49,48,65],[83,84,68,50,49,66],[84,85,69,51,50,67],[85,86,70,52,51,68],[86,87,71,53,52,69],[87,88,72,54,53,70],[88,89,73,55,54,71],[89,90,74,56,55,72],[90,255,255,57,56,73],[255,91,76,59,58,255],[91,92,77,60,59,75],[92,93,78,61,60,76],[93,94,79,62,61,77],[94,95,80,63,62,78],[95,96,81,64,63,79],[96,97,82,65,64,80],[97,98,83,66,65,81],[98,99,84,67,66,82],[99,100,85,68,67,83],[100,101,86,69,68,84],[101,102,87,70,69,85],[102,103,88,71,70,86],[103,104,89,72,71,87],[104,105,90,73,72,88],[105,255,255,74,73,89],[255,106,92,76,75,255],[106,107,93,77,76,91],[107,108,94,78,77,92],[108,109,95,79,78,93],[109,110,96,80,79,94],[110,111,97,81,80,95],[111,112,98,82,81,96],[112,113,99,83,82,97],[113,114,100,84,83,98],[114,115,101,85,84,99],[115,116,102,86,85,100],[116,117,103,87,86,101],[117,118,104,88,87,102],[118,119,105,89,88,103],[119,255,255,90,89,104],[255,120,107,92,91,255],[120,121,108,93,92,106],[121,122,109,94,93,107],[122,123,110,95,94,108],[123,124,111,96,95,109],[124,125,112,97,96,110],[125,126,113,98,97,111],[126,127,114,99,98,112],[127,128,115,100,99,113],[128,129,116,101,100,114],[129,130,117,102,101,115],[130,131,118,103,102,116],[131,132,119,104,103,117],[132,255,255,105,104,118],[133,134,121,107,106,255],[134,135,122,108,107,120],[135,136,123,109,108,121],[136,137,124,110,109,122],[137,138,125,111,110,123],[138,139,126,112,111,124],[139,140,127,113,112,125],[140,141,128,114,113,126],[141,142,129,115,114,127],[142,143,130,116,115,128],[143,144,131,117,116,129],[144,145,132,118,117,130],[145,146,255,119,118,131],[147,148,134,120,255,255],[148,149,135,121,120,133],[149,150,136,122,121,134],[150,151,137,123,122,135],[151,152,138,124,123,136],[152,153,139,125,124,137],[153,154,140,126,125,138],[154,155,141,127,126,139],[155,156,142,128,127,140],[156,157,143,129,128,141],[157,158,144,130,129,142],[158,159,145,131,130,143],[159,160,146,132,131,144],[160,161,255,255,132,145],[162,163,148,133,255,255],[163,164,149,134,133,147],[164,165,150,135,134,148],[165,166,151,136,135,149],[166,167,152,137,136,150],[167,168,153,138,137,151],[168,169,154,139,138,152],[169,170,155,140,139,153],[170,171,156,141,140,154],[171,172,157,142,141,155],[172,173,158,143,142,156],[173,174,159,144,143,157],[174,175,160,145,144,158],[175,176,161,146,145,159],[176,177,255,255,146,160],[178,179,163,147,255,255],[179,180,164,148,147,162],[180,181,165,149,148,163],[181,182,166,150,149,164],[182,183,167,151,150,165],[183,184,168,152,151,166],[184,185,169,153,152,167],[185,186,170,154,153,168],[186,187,171,155,154,169],[187,188,172,156,155,170],[188,189,173,157,156,171],[189,190,174,158,157,172],[190,191,175,159,158,173],[191,192,176,160,159,174],[192,193,177,161,160,175],[193,194,255,255,161,176],[195,196,179,162,255,255],[196,197,180,163,162,178],[197,198,181,164,163,179],[198,199,182,165,164,180],[199,200,183,166,165,181],[200,201,184,167,166,182],[201,202,185,168,167,183],[202,203,186,169,168,184],[203,204,187,170,169,185],[204,205,188,171,170,186],[205,206,189,172,171,187],[206,207,190,173,172,188],[207,208,191,174,173,189],[208,209,192,175,174,190],[209,210,193,176,175,191],[210,211,194,177,176,192],[211,212,255,255,177,193],[213,214,196,178,255,255],[214,215,197,179,178,195],[215,216,198,180,179,196],[216,217,199,181,180,197],[217,218,200,182,181,198],[218,219,201,183,182,199],[219,220,202,184,183,200],[220,221,203,185,184,201],[221,222,204,186,185,202],[222,223,205,187,186,203],[223,224,206,188,187,204],[224,225,207,189,188,205],[225,226,208,190,189,206],[226,227,209,191,190,207],[227,228,210,192,191,208],[228,229,211,193,192,209],[229,230,212,194,193,210],[230,231,255,255,194,211],[255,255,214,195,255,255],[255,255,215,196,195,213],[255,255,216,197,196,214],[255,255,217,198,197,215],[255,255,218,199,198,216],[255,255,219,200,199,217],[255,232,220,201,200,218],[232,233,221,202,201,219],[233,234,222,203,202,220],[234,235,223,204,203,221],[235,236,224,205,204,222],[236,237,225,206,205,223],[237,255,226,207,206,224],[255,255,227,208,207,225],[255,255,228,209,208,226],[255,255,229,210,209,227],[255,255,230,211,210,228],[255,255,231,212,211,229],[255,255,255,255,212,230],[255,238,233,220,219,255],[238,239,234,221,220,232],[239,240,235,222,221,233],[240,241,236,223,222,234],[241,242,237,224,223,235],[242,255,255,225,224,236],[255,243,239,233,232,255],[243,244,240,234,233,238],[244,245,241,235,234,239],[245,246,242,236,235,240],[246,255,255,237,236,241],[255,247,244,239,238,255],[247,248,245,240,239,243],[248,249,246,241,240,244],[249,255,255,242,241,245],[255,250,248,244,243,255],[250,251,249,245,244,247],[251,255,255,246,245,248],[255,252,251,248,247,255],[252,255,255,249,248,250],[255,255,255,251,250,255]],
-    p1_base: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 27, 28, 29, 30, 31, 32, 33],
-    p2_base: &[219, 220, 221, 222, 223, 224, 225, 232, 233, 234, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247, 248, 249, 250, 251, 252],
-    p1_distance_map: &[24, 23, 23, 22, 22, 22, 21, 21, 21, 21, 20, 20, 20, 20, 20, 19, 19, 19, 19, 19, 19, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 12, 12, 11, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 11, 12, 12, 11, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 11, 12, 12, 11, 10, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 10, 11, 12, 12, 11, 10, 9, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10, 11, 12, 12, 11, 10, 9, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 9, 10, 11, 12, 5, 5, 5, 5, 5, 5, 4, 4, 4, 4, 4, 3, 3, 3, 3, 2, 2, 2, 1, 1, 0],
-    p2_distance_map: &[0, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 4, 5, 5, 5, 5, 5, 5, 12, 11, 10, 9, 8, 7, 6, 6, 6, 6, 6, 6, 6, 7, 8, 9, 10, 11, 12, 12, 11, 10, 9, 8, 7, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10, 11, 12, 12, 11, 10, 9, 8, 8, 8, 8, 8, 8, 8, 8, 8, 9, 10, 11, 12, 12, 11, 10, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 10, 11, 12, 12, 11, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 11, 12, 12, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 11, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 17, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 19, 19, 19, 19, 19, 19, 20, 20, 20, 20, 20, 21, 21, 21, 21, 22, 22, 22, 23, 23, 24],
-    min_distance: 112
-};
+        self.bases.concat()
+    }
+
+    // the base of a player enlarged like the bases of plus boards, for handicap setups
+    pub fn plus_base(&self, player: usize) -> Vec<Position> {
+        base_plus(&self.ajd_matrix, &self.bases[player])
+    }
+
+    pub fn cube_coordinates(&self, pos: Position) -> (i32, i32, i32) {
+        let (q, r) = self.coordinates[pos as usize];
+        (q, r, -q - r)
+    }
+
+    pub fn position_at(&self, q: i32, r: i32) -> Option<Position> {
+        self.coordinates.iter().position(|&c| c == (q, r)).map(|i| i as Position)
+    }
+
+    // the number of single steps between the two positions if there were no holes, or None if one is off the board
+    pub fn hex_distance(&self, a: Position, b: Position) -> Option<u32> {
+        let ((q1, r1), (q2, r2)) = (*self.coordinates.get(a as usize)?, *self.coordinates.get(b as usize)?);
+        let (dq, dr) = (q2 - q1, r2 - r1);
+        Some((dq.unsigned_abs() + dr.unsigned_abs() + (dq + dr).unsigned_abs()) / 2)
+    }
+
+    // the direction (index into DIRECTIONS) from a to b, if b lies on one of the six straight lines from a
+    pub fn direction(&self, a: Position, b: Position) -> Option<usize> {
+        let steps = self.hex_distance(a, b)? as i32;
+        let ((q1, r1), (q2, r2)) = (self.coordinates[a as usize], self.coordinates[b as usize]);
+        if steps == 0 {
+            return None
+        }
+        DIRECTIONS.iter().position(|&(dq, dr)| (q1 + dq * steps, r1 + dr * steps) == (q2, r2))
+    }
+
+    // the positions strictly between a and b, if they are on a straight line without holes in between
+    pub fn line_of_sight(&self, a: Position, b: Position) -> Option<Vec<Position>> {
+        let direction = self.direction(a, b)?;
+        let mut result = vec![];
+        let mut cp = a;
+        for _ in 1..self.hex_distance(a, b)? {
+            cp = self.ajd_matrix[cp as usize][direction];
+            if cp == INVALID_POSITION {
+                return None
+            }
+            result.push(cp);
+        }
+        (self.ajd_matrix[cp as usize][direction] == b).then_some(result)
+    }
+}
+
+// the base and all its neighbours
+fn base_plus(ajd_matrix: &[[Position; 6]], base: &[Position]) -> Vec<Position> {
+    let mut result = base.to_vec();
+    for &p in base {
+        result.extend(ajd_matrix[p as usize].iter().copied().filter(|&n| n != INVALID_POSITION));
+    }
+    result.sort_unstable();
+    result.dedup();
+    result
+}
+
+// the zobrist number of a turn. Turns are unbounded, so they are hashed instead of looked up.
+pub fn zobrist_turn(turn: u32) -> u64 {
+    let mut state = turn as u64 ^ 0x6a09e667f3bcc908;
+    splitmix64(&mut state)
+}
+
+// continues an FNV-1a hash with more bytes
+pub(crate) fn fnv1a(hash: u32, bytes: impl IntoIterator<Item = u8>) -> u32 {
+    bytes.into_iter().fold(hash, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+// try the 12 rotations and reflections of the hex grid. A transform is a symmetry if it maps the board onto itself,
+// keeps the adjacency, and maps each player's base, target and distance map to that of the same or another player.
+fn find_symmetries(board: &Board) -> Vec<Symmetry> {
+    let n_players = board.n_players;
+    let mut result = vec![];
+
+    for reflect in [false, true] {
+        for rotation in 0..6 {
+            let transform = |(mut q, mut r): (i32, i32)| {
+                if reflect {
+                    r = -q - r
+                }
+                for _ in 0..rotation {
+                    (q, r) = (-r, q + r) // 60 degrees counter-clockwise
+                }
+                (q, r)
+            };
+
+            // the transforms are around the origin, so align the smallest coordinates to find the translation
+            let image: Vec<_> = board.coordinates.iter().map(|&c| transform(c)).collect();
+            let (q0, r0) = *board.coordinates.iter().min().unwrap();
+            let (q1, r1) = *image.iter().min().unwrap();
+            let Some(permutation) = image.iter().map(|&(q, r)| board.position_at(q + q0 - q1, r + r0 - r1)).collect::<Option<Vec<_>>>() else {
+                continue
+            };
+
+            let keeps_adjacency = (0..board.board_size).all(|pos| {
+                board.ajd_matrix[pos].iter().filter(|&&n| n != INVALID_POSITION).all(|&n| board.ajd_matrix[permutation[pos] as usize].contains(&permutation[n as usize]))
+            });
+            if !keeps_adjacency {
+                continue
+            }
+
+            let maps_onto = |from: &[Position], to: &[Position]| {
+                let mut image: Vec<_> = from.iter().map(|&p| permutation[p as usize]).collect();
+                image.sort_unstable();
+                image == to
+            };
+            let player_shift = (0..n_players).find(|&shift| (0..n_players).all(|i| {
+                let j = (i + shift) % n_players;
+                maps_onto(&board.bases[i], &board.bases[j]) && maps_onto(&board.targets[i], &board.targets[j]) &&
+                    (0..board.board_size).all(|pos| board.distance_maps[j][permutation[pos] as usize] == board.distance_maps[i][pos])
+            }));
+            if let Some(player_shift) = player_shift {
+                result.push(Symmetry { permutation, player_shift })
+            }
+        }
+    }
+
+    result
+}
+
+// assign coordinates by walking the adjacency from position 0, which is put at (0, 0)
+fn walk_coordinates(ajd_matrix: &[[Position; 6]]) -> Option<Vec<(i32, i32)>> {
+    let mut coordinates = vec![None; ajd_matrix.len()];
+    let mut queue = vec![0];
+    coordinates[0] = Some((0, 0));
+    while let Some(pos) = queue.pop() {
+        let (q, r) = coordinates[pos as usize]?;
+        for (direction, &neibour) in ajd_matrix[pos as usize].iter().enumerate() {
+            if neibour != INVALID_POSITION && coordinates[neibour as usize].is_none() {
+                coordinates[neibour as usize] = Some((q + DIRECTIONS[direction].0, r + DIRECTIONS[direction].1));
+                queue.push(neibour);
+            }
+        }
+    }
+    coordinates.into_iter().collect()
+}
+
+// saturating so that unreachable targets are reported by validate() instead of overflowing here
+fn min_distances(targets: &[Vec<Position>], distance_maps: &[Vec<u64>]) -> Vec<u64> {
+    targets.iter().zip(distance_maps).map(|(target, map)| target.iter().fold(0u64, |sum, &p| sum.saturating_add(map[p as usize]))).collect()
+}
+
+// the BFS distance to the tip of the target, which is the target position farthest from the base
+fn tip_distance(ajd_matrix: &[[Position; 6]], base: &[Position], target: &[Position]) -> Vec<u64> {
+    let from_base = bfs_distance_from(ajd_matrix, base);
+    match target.iter().max_by_key(|&&p| (from_base[p as usize], std::cmp::Reverse(p))) {
+        Some(&tip) => bfs_distance(ajd_matrix, tip),
+        None => vec![u64::MAX; ajd_matrix.len()]
+    }
+}
+
+// calculate the distance from each node to the target with BFS. Unreachable nodes get u64::MAX.
+fn bfs_distance(ajd_matrix: &[[Position; 6]], target: Position) -> Vec<u64> {
+    bfs_distance_from(ajd_matrix, &[target])
+}
+
+fn bfs_distance_from(ajd_matrix: &[[Position; 6]], sources: &[Position]) -> Vec<u64> {
+    let mut dist = vec![u64::MAX; ajd_matrix.len()];
+    let mut queue = VecDeque::from(sources.to_vec());
+    for &source in sources {
+        dist[source as usize] = 0;
+    }
+    while let Some(node) = queue.pop_front() {
+        for &neibour in &ajd_matrix[node as usize] {
+            if neibour != INVALID_POSITION && dist[neibour as usize] == u64::MAX {
+                dist[neibour as usize] = dist[node as usize] + 1;
+                queue.push_back(neibour);
+            }
+        }
+    }
+    dist
+}
+
+// the built-in boards used to be pasted from gen_board.jl, their sizes are kept here to check the generated ones.
+// They are also validated when generated.
+fn checked_size(board: Board, board_size: usize, n_pieces: usize, min_distance: u64) -> Board {
+    assert_eq!((board.board_size, board.n_pieces, board.min_distances.as_slice()), (board_size, n_pieces, &[min_distance, min_distance][..]));
+    board
+}
+
+pub static TINY_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(2, false), 37, 3, 2));
+pub static SMALL_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(3, false), 73, 6, 8));
+pub static STANDARD_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(4, false), 121, 10, 20));
+pub static LARGE_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(5, false), 181, 15, 40));
+pub static HUGE_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(6, false), 253, 21, 70));
+pub static TINY_PLUS_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(2, true), 37, 6, 8));
+pub static SMALL_PLUS_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(3, true), 73, 10, 20));
+pub static STANDARD_PLUS_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(4, true), 121, 15, 40));
+pub static LARGE_PLUS_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(5, true), 181, 21, 70));
+pub static HUGE_PLUS_BOARD: LazyLock<Board> = LazyLock::new(|| checked_size(Board::from_rank(6, true), 253, 28, 112));
+
+// the built-in board of the given rank, so that games on it can borrow it for 'static.
+// Boards for more than two players are generated on first use and kept forever.
+pub fn builtin_board(rank: usize, plus: bool, n_players: usize) -> Option<&'static Board> {
+    if n_players != 2 {
+        if !(2..=6).contains(&rank) || !matches!(n_players, 3 | 4 | 6) || plus && n_players > 3 {
+            return None
+        }
+
+        static BOARDS: Mutex<BTreeMap<(usize, bool, usize), &'static Board>> = Mutex::new(BTreeMap::new());
+        let mut boards = BOARDS.lock().unwrap();
+        let board = boards.entry((rank, plus, n_players)).or_insert_with(|| Box::leak(Box::new(Board::from_rank_with_players(rank, plus, n_players))));
+        return Some(*board)
+    }
+
+    let board: &'static LazyLock<Board> = match (rank, plus) {
+        (2, false) => &TINY_BOARD,
+        (3, false) => &SMALL_BOARD,
+        (4, false) => &STANDARD_BOARD,
+        (5, false) => &LARGE_BOARD,
+        (6, false) => &HUGE_BOARD,
+        (2, true) => &TINY_PLUS_BOARD,
+        (3, true) => &SMALL_PLUS_BOARD,
+        (4, true) => &STANDARD_PLUS_BOARD,
+        (5, true) => &LARGE_PLUS_BOARD,
+        (6, true) => &HUGE_PLUS_BOARD,
+        _ => return None
+    };
+    Some(&**board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the ten boards that used to be pasted from gen_board.jl
+    fn builtin_boards() -> Vec<&'static Board> {
+        (2..=6).flat_map(|rank| [false, true].map(|plus| builtin_board(rank, plus, 2).unwrap())).collect()
+    }
+
+    #[test]
+    fn generated_boards_match_the_old_literals() {
+        let literals: JsonValue = serde_json::from_str(include_str!("testdata/baseline_boards.json")).unwrap();
+        let as_vec = |json: &JsonValue| json.as_array().unwrap().iter().map(|x| x.as_u64().unwrap()).collect::<Vec<_>>();
+
+        for board in builtin_boards() {
+            let literal = &literals[&board.name];
+            assert_eq!(board.board_size as u64, literal["board_size"].as_u64().unwrap(), "{}", board.name);
+            assert_eq!(board.n_pieces as u64, literal["n_pieces"].as_u64().unwrap(), "{}", board.name);
+            let ajd_matrix = board.ajd_matrix.iter().flatten().map(|&p| p as u64).collect::<Vec<_>>();
+            let literal_ajd_matrix = literal["ajd_matrix"].as_array().unwrap().iter().flat_map(as_vec).collect::<Vec<_>>();
+            assert_eq!(ajd_matrix, literal_ajd_matrix, "{}", board.name);
+            for (player, key) in ["p1", "p2"].into_iter().enumerate() {
+                assert_eq!(board.bases[player].iter().map(|&p| p as u64).collect::<Vec<_>>(), as_vec(&literal[format!("{key}_base")]), "{}", board.name);
+                assert_eq!(board.distance_maps[player], as_vec(&literal[format!("{key}_distance_map")]), "{}", board.name);
+                assert_eq!(board.min_distances[player], literal["min_distance"].as_u64().unwrap(), "{}", board.name);
+            }
+        }
+    }
+
+    #[test]
+    fn relations_of_off_board_positions_are_none() {
+        let board = builtin_board(2, false, 2).unwrap();
+        let off = board.board_size as Position;
+        assert_eq!(board.hex_distance(0, 0), Some(0));
+        for (a, b) in [(0, off), (off, 0), (INVALID_POSITION, INVALID_POSITION)] {
+            assert_eq!(board.hex_distance(a, b), None);
+            assert_eq!(board.direction(a, b), None);
+            assert_eq!(board.line_of_sight(a, b), None);
+        }
+    }
+
+    #[test]
+    fn builtin_boards_are_valid() {
+        for board in builtin_boards() {
+            assert_eq!(board.validate(), Ok(()), "{}", board.name);
+        }
+        for (rank, plus, n_players) in [(2, false, 3), (4, true, 3), (3, false, 4), (3, false, 6)] {
+            builtin_board(rank, plus, n_players).unwrap().validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn validate_rejects_asymmetric_adjacency() {
+        let mut board = Board::from_rank(2, false);
+        let direction = (0..6).find(|&direction| board.ajd_matrix[0][direction] != INVALID_POSITION).unwrap();
+        let neibour = board.ajd_matrix[0][direction];
+        board.ajd_matrix[neibour as usize][(direction + 3) % 6] = INVALID_POSITION;
+        assert_eq!(board.validate(), Err(BoardError::AsymmetricAdjacency { pos: 0, direction, neibour }));
+    }
+
+    #[test]
+    fn validate_rejects_wrong_distance_maps() {
+        let mut board = Board::from_rank(2, false);
+        let pos = board.bases[1][0];
+        let expected = board.distance_maps[1][pos as usize];
+        board.distance_maps[1][pos as usize] += 1;
+        assert_eq!(board.validate(), Err(BoardError::DistanceMap { player: 1, pos, expected, actual: expected + 1 }));
+    }
+
+    #[test]
+    fn validate_rejects_wrong_min_distances() {
+        let mut board = Board::from_rank(3, true);
+        let expected = board.min_distances[0];
+        board.min_distances[0] -= 1;
+        assert_eq!(board.validate(), Err(BoardError::MinDistance { player: 0, expected, actual: expected - 1 }));
+    }
+
+    #[test]
+    fn boards_with_unreachable_targets_are_rejected() {
+        // two separate pairs of cells, the base of player 1 is the target of player 2 and the other way around
+        let json = serde_json::json!({
+            "name": "split",
+            "adjacency": [[null, null, 1, null, null, null], [null, null, null, null, null, 0], [null, null, 3, null, null, null], [null, null, null, null, null, 2]],
+            "coordinates": [[0, 0], [1, 0], [5, 0], [6, 0]],
+            "bases": [[0], [3]],
+        });
+        assert_eq!(Board::from_json(&json).err(), Some(BoardError::NoPathToTarget { player: 0, pos: 0 }));
+    }
+
+    #[test]
+    fn inverse_symmetries_undo_the_symmetries() {
+        for (rank, plus, n_players) in [(3, false, 2), (3, true, 2), (3, false, 3), (3, false, 4), (2, false, 6)] {
+            let board = builtin_board(rank, plus, n_players).unwrap();
+            for symmetry in &board.symmetries {
+                let inverse = symmetry.inverse(n_players);
+                for pos in 0..board.board_size as Position {
+                    assert_eq!(inverse.apply(symmetry.apply(pos)), pos, "{}", board.name);
+                    assert_eq!(symmetry.apply(inverse.apply(pos)), pos, "{}", board.name);
+                }
+                assert_eq!((symmetry.player_shift + inverse.player_shift) % n_players, 0, "{}", board.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+// scores of the *_poll sessions, keyed by the zobrist hash of the game keys (see Game::key_hash).
+// In checked mode the keys are kept as well, and a hash collision panics. It is meant for debugging.
+#[derive(Default)]
+pub struct ScoreCache {
+    scores: HashMap<u64, f64>,
+    keys: Option<HashMap<u64, Vec<u8>>>,
+}
+
+impl ScoreCache {
+    pub fn new(checked: bool) -> ScoreCache {
+        ScoreCache { scores: HashMap::new(), keys: checked.then(HashMap::new) }
+    }
+
+    // `key` is only called in checked mode
+    pub fn get(&self, hash: u64, key: impl FnOnce() -> Vec<u8>) -> Option<f64> {
+        let score = self.scores.get(&hash).copied()?;
+        if let Some(keys) = &self.keys {
+            check_collision(&keys[&hash], &key(), hash);
+        }
+        Some(score)
+    }
+
+    pub fn insert(&mut self, hash: u64, key: Vec<u8>, score: f64) {
+        if let Some(keys) = &mut self.keys {
+            if let Some(stored) = keys.get(&hash) {
+                check_collision(stored, &key, hash);
+            }
+            keys.insert(hash, key);
+        }
+        self.scores.insert(hash, score);
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+}
+
+fn check_collision(stored: &[u8], key: &[u8], hash: u64) {
+    assert!(stored == key, "hash collision: keys {stored:?} and {key:?} both hash to {hash:#018x}");
+}
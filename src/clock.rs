@@ -0,0 +1,15 @@
+// milliseconds since an arbitrary start, for the search budgets. wasm32-unknown-unknown has no clock,
+// so the web client provides `now_ms` in the "env" imports, e.g. with performance.now().
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_millis() -> f64 {
+    static START: std::sync::LazyLock<std::time::Instant> = std::sync::LazyLock::new(std::time::Instant::now);
+    START.elapsed().as_secs_f64() * 1000.
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn now_millis() -> f64 {
+    extern "C" {
+        fn now_ms() -> f64;
+    }
+    unsafe { now_ms() }
+}
@@ -1,162 +1,678 @@
-use crate::{INVALID_POSITION, Position, board::Board};
+use std::collections::VecDeque;
 
-#[derive(Clone)]
+use serde_json::Value as JsonValue;
+
+use crate::{INVALID_POSITION, Position, bitboard::Bitboard, board::{Board, Symmetry, fnv1a, zobrist_turn}, movegen::{MoveCursor, MoveGen, MoveOrder}};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Action(pub Position, pub Position); // from, to
 
+// what make_move changed, so unmake_move can restore it
+#[derive(Clone, Copy, Debug)]
+pub struct Undo {
+    action: Action,
+    player: usize,
+    turn: usize,
+    n_finished: usize,
+}
+
+// which hops are allowed. A hop always jumps over exactly one piece, with the same number of empty cells before and after it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Ruleset {
+    pub max_hop_distance: Option<usize>, // the most empty cells between the hopping piece and the jumped piece. Some(0) is the classic short hop, None allows hops of any length
+    pub max_hops: Option<usize>, // the longest chain of hops in one move, None for unlimited
+}
+
+impl Ruleset {
+    pub const LONG: Ruleset = Ruleset { max_hop_distance: None, max_hops: None };
+    pub const SHORT: Ruleset = Ruleset { max_hop_distance: Some(0), max_hops: None };
+
+    // {"max_hop_distance": 0, "max_hops": 3}, a missing or null field means unlimited
+    pub fn from_json(json: &JsonValue) -> Result<Ruleset, String> {
+        let limit = |field: &str| match &json[field] {
+            JsonValue::Null => Ok(None),
+            value => value.as_u64().map(|n| Some(n as usize)).ok_or(format!("{field} must be a non-negative integer or null")),
+        };
+        Ok(Ruleset { max_hop_distance: limit("max_hop_distance")?, max_hops: limit("max_hops")? })
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset::LONG
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EndReason {
+    TargetFilled, // the winner filled their target with their own pieces
+    TargetBlocked, // the winner's target is fully occupied, partly by opponents' pieces
+    NoMoves, // the player to move cannot move
+    Repetition, // the same position occurred for the third time with the same player to move
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    Ongoing,
+    Ended { winner: Option<usize>, reason: EndReason }, // no winner means a tie
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveError {
+    GameEnded,
+    OutOfBoard { pos: Position },
+    NotOwnPiece { player: usize, from: Position }, // no piece of the player to move at `from`
+    Unreachable { from: Position, to: Position },
+}
+
+impl MoveError {
+    // the error code reported through the ffi, 0 is reserved for success
+    pub fn code(&self) -> u8 {
+        match self {
+            MoveError::GameEnded => 1,
+            MoveError::OutOfBoard { .. } => 2,
+            MoveError::NotOwnPiece { .. } => 3,
+            MoveError::Unreachable { .. } => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MoveError::GameEnded => write!(f, "the game has already ended"),
+            MoveError::OutOfBoard { pos } => write!(f, "position {pos} is outside of the board"),
+            MoveError::NotOwnPiece { player, from } => write!(f, "there is no piece of player {player} at position {from}"),
+            MoveError::Unreachable { from, to } => write!(f, "the piece at position {from} cannot move to position {to}"),
+        }
+    }
+}
+
+// keys are [KEY_VERSION, the board fingerprint (4 bytes), turn (4 bytes), pieces...], integers in little endian.
+// The fingerprint also covers the numbers of pieces of a setup, see key_fingerprint.
+pub const KEY_VERSION: u8 = 1;
+pub const KEY_HEADER_SIZE: usize = 9;
+
+pub fn key_turn(key: &[u8]) -> u32 {
+    u32::from_le_bytes(key[5..9].try_into().unwrap())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyError {
+    Length { expected: usize, actual: usize },
+    Version { version: u8 },
+    Board { expected: u32, actual: u32 }, // the fingerprints differ, the key is for another board or other numbers of pieces
+    PositionOutOfBoard { pos: Position },
+    Unsorted { player: usize },
+    DuplicatedPosition { pos: Position },
+}
+
+impl std::fmt::Display for KeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeyError::Length { expected, actual } => write!(f, "the key has {actual} bytes instead of {expected}"),
+            KeyError::Version { version } => write!(f, "key version {version} is not supported, the current version is {KEY_VERSION}"),
+            KeyError::Board { expected, actual } => write!(f, "the key has fingerprint {actual:#010x}, but the game has {expected:#010x}, it is for another board or other numbers of pieces"),
+            KeyError::PositionOutOfBoard { pos } => write!(f, "position {pos} in the key is outside of the board"),
+            KeyError::Unsorted { player } => write!(f, "the pieces of player {player} in the key are not sorted"),
+            KeyError::DuplicatedPosition { pos } => write!(f, "position {pos} is occupied twice in the key"),
+        }
+    }
+}
+
+// the starting pieces of a game that does not start from the bases of the board, e.g. a handicap game.
+// Players may have different numbers of pieces. See Game::with_setup.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Setup {
+    pub pieces: Vec<Vec<Position>>, // the starting pieces of each player in the order of moving, sorted
+    finish_distances: Vec<u64>, // the smallest possible distance of each player, which is board.min_distances for a full base
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Handicap {
+    pub extra_pieces: isize, // the number of pieces compared to the base, negative for fewer pieces
+    pub head_start: i64, // how much less distance the player has to cover than from the base, see adjusted_distance
+}
+
+#[derive(Debug)]
+pub enum SetupError {
+    Definition(String), // the json setup is malformed
+    PlayerCount { expected: usize, actual: usize },
+    NoPieces { player: usize },
+    PositionOutOfBoard { player: usize, pos: Position },
+    Overlap { pos: Position },
+    AlreadyFinished { player: usize },
+}
+
+impl std::fmt::Display for SetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SetupError::Definition(message) => write!(f, "invalid setup: {message}"),
+            SetupError::PlayerCount { expected, actual } => write!(f, "the setup has pieces for {actual} players, but the board has {expected} players"),
+            SetupError::NoPieces { player } => write!(f, "player {player} has no pieces"),
+            SetupError::PositionOutOfBoard { player, pos } => write!(f, "position {pos} of player {player} is outside of the board"),
+            SetupError::Overlap { pos } => write!(f, "position {pos} is occupied more than once"),
+            SetupError::AlreadyFinished { player } => write!(f, "player {player} has already finished"),
+        }
+    }
+}
+
+impl Setup {
+    fn new(board: &Board, pieces: Vec<Vec<Position>>) -> Setup {
+        let finish_distances = pieces.iter().enumerate().map(|(player, pieces)| finish_distance(board, player, pieces.len())).collect();
+        Setup { pieces, finish_distances }
+    }
+
+    // reads the starting pieces for Game::with_setup from a JSON array with an entry for each player, which is either
+    // a list of positions, or an object that starts from the base ("standard", the default) or the plus base ("plus")
+    // and removes and adds some positions. For example, a plus base against a base without two pieces and one piece advanced:
+    // [{"base": "plus"}, {"remove": [111, 112, 120], "add": [85]}]
+    pub fn pieces_from_json(board: &Board, json: &JsonValue) -> Result<Vec<Vec<Position>>, SetupError> {
+        Setup::parse_json(board, json).map_err(SetupError::Definition)
+    }
+
+    fn parse_json(board: &Board, json: &JsonValue) -> Result<Vec<Vec<Position>>, String> {
+        let parse_positions = |json: &JsonValue, what: &str| -> Result<Vec<Position>, String> {
+            json.as_array().and_then(|list| list.iter().map(|p| p.as_u64().filter(|&p| p < INVALID_POSITION as u64).map(|p| p as Position)).collect())
+                .ok_or(format!("{what} must be an array of positions"))
+        };
+
+        let players = json.as_array().ok_or("the setup must be an array with an entry for each player")?;
+        players.iter().enumerate().map(|(player, entry)| {
+            if entry.is_array() {
+                return parse_positions(entry, &format!("the pieces of player {player}"))
+            }
+
+            let mut pieces = match &entry["base"] {
+                JsonValue::Null => board.bases.get(player).cloned().unwrap_or_default(),
+                JsonValue::String(base) if base == "standard" => board.bases.get(player).cloned().unwrap_or_default(),
+                JsonValue::String(base) if base == "plus" && player < board.n_players => board.plus_base(player),
+                _ => return Err(format!("\"base\" of player {player} must be \"standard\" or \"plus\""))
+            };
+            if !entry["remove"].is_null() {
+                for pos in parse_positions(&entry["remove"], &format!("\"remove\" of player {player}"))? {
+                    let i = pieces.iter().position(|&piece| piece == pos).ok_or(format!("player {player} has no piece at position {pos} to remove"))?;
+                    pieces.remove(i);
+                }
+            }
+            if !entry["add"].is_null() {
+                pieces.extend(parse_positions(&entry["add"], &format!("\"add\" of player {player}"))?);
+            }
+            Ok(pieces)
+        }).collect()
+    }
+
+    // compares the setup of a player with starting from the base
+    pub fn handicap(&self, board: &Board, player: usize) -> Handicap {
+        let remaining = |pieces: &[Position]| {
+            let distance: u64 = pieces.iter().map(|&piece| board.distance_maps[player][piece as usize]).sum();
+            distance as i64 - finish_distance(board, player, pieces.len()) as i64
+        };
+        Handicap {
+            extra_pieces: self.pieces[player].len() as isize - board.n_pieces as isize,
+            head_start: remaining(&board.bases[player]) - remaining(&self.pieces[player]),
+        }
+    }
+}
+
+// the smallest distance sum of `n` pieces of a player, i.e. when they are packed at the tip of the target
+fn finish_distance(board: &Board, player: usize, n: usize) -> u64 {
+    let mut distances = board.distance_maps[player].clone();
+    distances.sort_unstable();
+    distances[..n].iter().sum()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EditError {
+    OutOfBoard { pos: Position },
+    Occupied { pos: Position },
+    NoPiece { pos: Position },
+    NoPlayer { player: usize },
+    LastPiece { player: usize }, // every player keeps at least one piece
+    Finished { player: usize }, // a finished player cannot move next
+}
+
+impl EditError {
+    // the error code reported through the ffi, 0 is reserved for success
+    pub fn code(&self) -> u8 {
+        match self {
+            EditError::OutOfBoard { .. } => 1,
+            EditError::Occupied { .. } => 2,
+            EditError::NoPiece { .. } => 3,
+            EditError::NoPlayer { .. } => 4,
+            EditError::LastPiece { .. } => 5,
+            EditError::Finished { .. } => 6,
+        }
+    }
+}
+
+impl std::fmt::Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EditError::OutOfBoard { pos } => write!(f, "position {pos} is outside of the board"),
+            EditError::Occupied { pos } => write!(f, "position {pos} is already occupied"),
+            EditError::NoPiece { pos } => write!(f, "there is no piece at position {pos}"),
+            EditError::NoPlayer { player } => write!(f, "there is no player {player}"),
+            EditError::LastPiece { player } => write!(f, "player {player} must keep at least one piece"),
+            EditError::Finished { player } => write!(f, "player {player} has finished and cannot move next"),
+        }
+    }
+}
+
+// why a position cannot occur in a game that started from the bases, see check_reachable
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Unreachable {
+    PieceCount { player: usize, expected: usize, actual: usize },
+    TooManyMoved { player: usize, moved: usize, moves: usize },
+    SeveralFinished, // a two-player game ends when the first player finishes
+}
+
+impl std::fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Unreachable::PieceCount { player, expected, actual } => write!(f, "player {player} has {actual} pieces instead of {expected}"),
+            Unreachable::TooManyMoved { player, moved, moves } => write!(f, "player {player} has {moved} pieces outside of their base, but has made at most {moves} moves"),
+            Unreachable::SeveralFinished => write!(f, "both players have finished, but the game ends when the first one finishes"),
+        }
+    }
+}
+
 #[derive(Clone)]
-pub struct Game {
-    pub board: &'static Board,
-    pub turn: usize, // Initial state (no players has moved and player 1 is about to move next) has turn = 0.
-    pub pieces: Vec<Position>, // first half are the pieces of p1 and second half are p2. Both sorted seperately.
+pub struct Game<'a> {
+    pub board: &'a Board,
+    pub turn: usize, // Initial state (no players has moved and player 1 is about to move next) has turn = 0. Finished players are skipped.
+    pub pieces: Vec<Position>, // the pieces of each player in turn, each part has board.n_pieces pieces (or as many as in the setup) and is sorted seperately.
+    pub finished: Vec<usize>, // players that have reached their targets, in the order of finishing
+    pub ruleset: Ruleset,
+    pub history: Vec<u64>, // position hashes (see position_hash) of every earlier turn, oldest first
+    pub setup: Option<Setup>, // None if the game started from the bases
+    distances: Vec<u64>, // the distance of each player, maintained on every move
+    occupancy: Bitboard, // all pieces, maintained on every move
+    player_masks: Vec<Bitboard>, // the pieces of each player, maintained on every move
+    pieces_hash: u64, // the zobrist hash of the pieces, maintained on every move
 }
 
-impl Game {
-    pub fn new(board: &'static Board) -> Self {
-        Game { board, turn: 0, pieces: board.starting_pieces() }
+impl<'a> Game<'a> {
+    pub fn new(board: &'a Board) -> Self {
+        Game::with_ruleset(board, Ruleset::default())
+    }
+
+    pub fn with_ruleset(board: &'a Board, ruleset: Ruleset) -> Self {
+        Game { board, turn: 0, pieces: board.starting_pieces(), finished: vec![], ruleset, history: vec![], setup: None, distances: vec![], occupancy: Bitboard::EMPTY, player_masks: vec![], pieces_hash: 0 }.refreshed()
+    }
+
+    // a game that starts from the given pieces of each player instead of the bases
+    pub fn with_setup(board: &'a Board, ruleset: Ruleset, mut pieces: Vec<Vec<Position>>) -> Result<Self, SetupError> {
+        if pieces.len() != board.n_players {
+            return Err(SetupError::PlayerCount { expected: board.n_players, actual: pieces.len() })
+        }
+        let mut occupied = Bitboard::EMPTY;
+        for (player, pieces) in pieces.iter_mut().enumerate() {
+            if pieces.is_empty() {
+                return Err(SetupError::NoPieces { player })
+            }
+            pieces.sort_unstable();
+            for &pos in pieces.iter() {
+                if pos as usize >= board.board_size {
+                    return Err(SetupError::PositionOutOfBoard { player, pos })
+                }
+                if occupied.contains(pos) {
+                    return Err(SetupError::Overlap { pos })
+                }
+                occupied.insert(pos);
+            }
+        }
+
+        let game = Game { board, turn: 0, pieces: pieces.concat(), finished: vec![], ruleset, history: vec![], setup: Some(Setup::new(board, pieces)), distances: vec![], occupancy: Bitboard::EMPTY, player_masks: vec![], pieces_hash: 0 }.refreshed();
+        if let Some(player) = (0..board.n_players).find(|&player| game.is_finished(player)) {
+            return Err(SetupError::AlreadyFinished { player })
+        }
+        Ok(game)
+    }
+
+    // recomputes the cached distances and masks from the pieces
+    fn refreshed(mut self) -> Self {
+        self.distances = (0..self.board.n_players).map(|player| {
+            self.pieces_slice(player).iter().map(|&p| self.board.distance_maps[player][p as usize]).sum()
+        }).collect();
+        self.player_masks = (0..self.board.n_players).map(|player| Bitboard::from_positions(self.pieces_slice(player))).collect();
+        self.occupancy = self.player_masks.iter().fold(Bitboard::EMPTY, |all, &mask| all | mask);
+        self.pieces_hash = (0..self.board.n_players).flat_map(|player| self.pieces_slice(player).iter().map(move |&piece| (player, piece)))
+            .fold(0, |hash, (player, piece)| hash ^ self.board.zobrist_pieces[player][piece as usize]);
+        self
+    }
+
+    pub fn player_to_move(&self) -> usize {
+        self.turn % self.board.n_players
     }
 
     pub fn is_p1_moving_next(&self) -> bool {
-        self.turn % 2 == 0
+        self.player_to_move() == 0
     }
 
     pub fn is_p2_moving_next(&self) -> bool {
-        self.turn % 2 == 1
+        self.player_to_move() == 1
     }
 
-    pub fn p1_pieces_slice(&self) -> &[u8] {
-        &self.pieces[..self.board.n_pieces]
+    pub fn pieces_slice(&self, player: usize) -> &[u8] {
+        &self.pieces[self.pieces_range(player)]
     }
 
-    pub fn p1_pieces_slice_mut(&mut self) -> &mut [u8] {
-        &mut self.pieces[..self.board.n_pieces]
+    fn pieces_slice_mut(&mut self, player: usize) -> &mut [u8] {
+        let range = self.pieces_range(player);
+        &mut self.pieces[range]
     }
 
-    pub fn p2_pieces_slice(&self) -> &[u8] {
-        &self.pieces[self.board.n_pieces..]
+    fn pieces_range(&self, player: usize) -> std::ops::Range<usize> {
+        match &self.setup {
+            None => player * self.board.n_pieces..(player + 1) * self.board.n_pieces,
+            Some(setup) => {
+                let start = setup.pieces[..player].iter().map(Vec::len).sum();
+                start..start + setup.pieces[player].len()
+            }
+        }
+    }
+
+    pub fn p1_pieces_slice(&self) -> &[u8] {
+        self.pieces_slice(0)
     }
 
-    pub fn p2_pieces_slice_mut(&mut self) -> &mut [u8] {
-        &mut self.pieces[self.board.n_pieces..]
+    pub fn p2_pieces_slice(&self) -> &[u8] {
+        self.pieces_slice(1)
     }
 
     pub fn has_piece(&self, piece: Position) -> bool {
-        self.pieces.contains(&piece) // or two binary searches?
+        self.occupancy.contains(piece)
     }
 
     pub fn move_to(&self, from: Position, to: Position) -> Self {
         let mut result = self.clone();
-        result.turn += 1;
+        result.make_move(from, to);
+        result
+    }
 
-        let moving_slice = if self.is_p1_moving_next() {
-            result.p1_pieces_slice_mut()
-        } else {
-            result.p2_pieces_slice_mut()
-        };
+    // move_to, but only if the move is legal
+    pub fn try_move(&self, from: Position, to: Position) -> Result<Game<'a>, MoveError> {
+        if self.is_ended() {
+            return Err(MoveError::GameEnded)
+        }
+        if let Some(pos) = [from, to].into_iter().find(|&pos| pos as usize >= self.board.board_size) {
+            return Err(MoveError::OutOfBoard { pos })
+        }
+        let player = self.player_to_move();
+        if !self.player_masks[player].contains(from) {
+            return Err(MoveError::NotOwnPiece { player, from })
+        }
+        if from == to || self.possible_moves_with_path(from)[to as usize] == INVALID_POSITION {
+            return Err(MoveError::Unreachable { from, to })
+        }
+        Ok(self.move_to(from, to))
+    }
+
+    // moves in place. Pass the returned record to unmake_move to take the move back.
+    pub fn make_move(&mut self, from: Position, to: Position) -> Undo {
+        let player = self.player_to_move();
+        let undo = Undo { action: Action(from, to), player, turn: self.turn, n_finished: self.finished.len() };
+        self.history.push(self.position_hash());
+        self.turn += 1;
+        self.replace_piece(player, from, to);
+
+        // two-player games simply end when one finishes. Otherwise finished players are recorded and do not move anymore.
+        // A move can also finish other players by filling their targets, they are recorded after the mover.
+        if self.board.n_players > 2 {
+            for p in (0..self.board.n_players).map(|i| (player + i) % self.board.n_players) {
+                if !self.finished.contains(&p) && self.is_finished(p) {
+                    self.finished.push(p);
+                }
+            }
+            while self.finished.contains(&self.player_to_move()) && !self.is_ended() {
+                self.turn += 1;
+            }
+        }
 
-        let from_idx = moving_slice.binary_search(&from).unwrap();
-        moving_slice[from_idx] = to;
-        moving_slice.sort_unstable();
+        undo
+    }
+
+    // must be called in the reverse order of make_move
+    pub fn unmake_move(&mut self, undo: Undo) {
+        let Action(from, to) = undo.action;
+        self.replace_piece(undo.player, to, from);
+        self.turn = undo.turn;
+        self.finished.truncate(undo.n_finished);
+        self.history.pop();
+    }
 
+    // runs f on the position after the move and takes the move back
+    pub fn with_move<T>(&mut self, Action(from, to): Action, f: impl FnOnce(&mut Self) -> T) -> T {
+        let undo = self.make_move(from, to);
+        let result = f(self);
+        self.unmake_move(undo);
         result
     }
 
+    // keeps the slice sorted by shifting the neighbours instead of sorting again, and updates the caches
+    fn replace_piece(&mut self, player: usize, from: Position, to: Position) {
+        let distance_map = &self.board.distance_maps[player];
+        self.distances[player] = self.distances[player] + distance_map[to as usize] - distance_map[from as usize];
+        for mask in [&mut self.occupancy, &mut self.player_masks[player]] {
+            mask.remove(from);
+            mask.insert(to);
+        }
+        self.pieces_hash ^= self.board.zobrist_pieces[player][from as usize] ^ self.board.zobrist_pieces[player][to as usize];
+
+        let slice = self.pieces_slice_mut(player);
+        let mut i = slice.binary_search(&from).unwrap();
+        while i > 0 && slice[i - 1] > to {
+            slice[i] = slice[i - 1];
+            i -= 1;
+        }
+        while i + 1 < slice.len() && slice[i + 1] < to {
+            slice[i] = slice[i + 1];
+            i += 1;
+        }
+        slice[i] = to;
+    }
+
+    pub fn distance(&self, player: usize) -> u64 {
+        self.distances[player]
+    }
+
     pub fn p1_distance(&self) -> u64 {
-        self.p1_pieces_slice().iter().map(|&p| self.board.p1_distance_map[p as usize]).sum()
+        self.distance(0)
     }
 
     pub fn p2_distance(&self) -> u64 {
-        self.p2_pieces_slice().iter().map(|&p| self.board.p2_distance_map[p as usize]).sum()
+        self.distance(1)
     }
 
-    pub fn expand(&self, record_actions: bool) -> (Vec<Game>, Vec<Action>) {
-        // early finish
-        if self.p1_distance() == self.board.min_distance || self.p2_distance() == self.board.min_distance {
-            return (vec![], vec![])
+    // the distance shifted so that every player finishes at board.min_distances, which makes the distances of
+    // players with different numbers of pieces comparable. It is the same as distance without a setup.
+    pub fn adjusted_distance(&self, player: usize) -> u64 {
+        match &self.setup {
+            None => self.distances[player],
+            Some(setup) => self.distances[player] + self.board.min_distances[player] - setup.finish_distances[player],
         }
+    }
 
-        let mut next_states = vec![];
-        let mut actions = vec![];
+    // a player finishes when every cell of their target is occupied and at least one of them by their own piece,
+    // so parking a piece in an opponent's target cannot stall the game. A player with fewer pieces than the target
+    // (see with_setup) also finishes when all their pieces are in the target.
+    pub fn is_finished(&self, player: usize) -> bool {
+        let (target, own) = (self.board.target_masks[player], self.player_masks[player]);
+        target.is_subset(&self.occupancy) && !(target & own).is_empty() || own.is_subset(&target)
+    }
 
-        let moving_slice = if self.is_p1_moving_next() {
-            self.p1_pieces_slice()
+    // the game ends when all but one players finished, or by threefold repetition.
+    // It also ends when the player to move is stuck, which is checked in `actions`.
+    pub fn is_ended(&self) -> bool {
+        self.is_all_finished() || self.is_threefold_repetition()
+    }
+
+    fn is_all_finished(&self) -> bool {
+        if self.board.n_players > 2 {
+            self.finished.len() + 1 >= self.board.n_players // a finished target may be vacated later, so the record is used
         } else {
-            self.p2_pieces_slice()
-        };
+            (0..self.board.n_players).any(|player| self.is_finished(player))
+        }
+    }
+
+    // finished players in the order of finishing, followed by the others sorted by their distances
+    pub fn finishing_order(&self) -> Vec<usize> {
+        let mut order = self.finished.clone();
+        order.extend((0..self.board.n_players).filter(|&player| !self.finished.contains(&player) && self.is_finished(player))); // two-player games do not record them
+        let mut rest: Vec<_> = (0..self.board.n_players).filter(|player| !order.contains(player)).collect();
+        rest.sort_by_key(|&player| self.adjusted_distance(player));
+        order.extend(rest);
+        order
+    }
 
-        for &piece in moving_slice {
-            let paths = self.possible_moves_with_path(piece);
+    pub fn status(&self) -> Status {
+        let order = self.finishing_order();
+        let winner = order[0];
 
-            for dest in paths.iter().enumerate().filter(|&(dest, from)| *from != INVALID_POSITION && dest as u8 != piece).map(|(dest, _)| dest as Position) {
-                next_states.push(self.move_to(piece, dest));
-                if record_actions {
-                    actions.push(Action(piece, dest));
-                }
-            }
+        if self.is_all_finished() {
+            let (target, own) = (self.board.target_masks[winner], self.player_masks[winner]);
+            let reason = if own.is_subset(&target) || target.is_subset(&own) {
+                EndReason::TargetFilled
+            } else {
+                EndReason::TargetBlocked
+            };
+            return Status::Ended { winner: Some(winner), reason }
         }
 
-        if next_states.is_empty() {
-            return (vec![], vec![])
+        // a draw among the players still playing
+        if self.is_threefold_repetition() {
+            return Status::Ended { winner: self.finished.first().copied(), reason: EndReason::Repetition }
         }
 
-        (next_states, actions)
+        if !self.actions().is_empty() {
+            return Status::Ongoing
+        }
+
+        // the player to move is stuck. The first finished player wins, or the closest one if nobody finished
+        let tie = self.finished.is_empty() && self.adjusted_distance(winner) == self.adjusted_distance(order[1]);
+        Status::Ended { winner: if tie { None } else { Some(winner) }, reason: EndReason::NoMoves }
     }
 
-    pub fn expand_forward_only(&self, record_actions: bool) -> (Vec<Game>, Vec<Action>) {
-        let (next_states, actions) = self.expand(record_actions);
-        let (forward_only_next_states, forward_only_actions): (Vec<Game>, Vec<Action>) = next_states.iter().zip(actions.iter())
-            .filter(|(next_state, _)| {
-                if self.is_p1_moving_next() {
-                    next_state.p1_distance() < self.p1_distance()
-                } else {
-                    next_state.p2_distance() < self.p2_distance()
-                }
-            })
-            .map(|(next_state, action)| (next_state.clone(), action.clone()))
-            .unzip();
-        if forward_only_next_states.is_empty() {
-            (next_states, actions)
+    // identifies the pieces and the player to move, which is all that matters for repetitions
+    pub fn position_hash(&self) -> u64 {
+        self.pieces_hash ^ self.board.zobrist_seats[self.player_to_move()]
+    }
+
+    // identifies the key (see key), used by the score caches instead of the key itself
+    pub fn key_hash(&self) -> u64 {
+        self.pieces_hash ^ zobrist_turn(self.turn as u32)
+    }
+
+    // how many times the current position occurred before
+    pub fn repetitions(&self) -> usize {
+        let hash = self.position_hash();
+        self.history.iter().filter(|&&h| h == hash).count()
+    }
+
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetitions() >= 2
+    }
+
+    // the legal moves of the player to move, empty if the game has ended
+    pub fn actions(&self) -> Vec<Action> {
+        self.move_gen(None).collect()
+    }
+
+    // the legal moves of the player to move, generated one at a time in the given order, natural by default
+    pub fn move_gen(&self, order: Option<MoveOrder>) -> MoveGen<'_, 'a> {
+        MoveGen::new(self, order.unwrap_or_default())
+    }
+
+    // the moves that reduce the distance of the player to move, or all moves if there is none
+    pub fn actions_forward_only(&self) -> Vec<Action> {
+        let actions = self.actions();
+        let distance_map = &self.board.distance_maps[self.player_to_move()];
+        let forward_only_actions: Vec<_> = actions.iter().filter(|&&Action(from, to)| distance_map[to as usize] < distance_map[from as usize]).copied().collect();
+        if forward_only_actions.is_empty() {
+            actions
         } else {
-            (forward_only_next_states, forward_only_actions)
+            forward_only_actions
         }
     }
 
+    // the cells an action visits, from its start to its end, following the hops found by possible_moves_with_path
+    pub fn action_path(&self, Action(from, to): Action) -> Vec<Position> {
+        path_to(&self.possible_moves_with_path(from), from, to)
+    }
+
+    // like expand with record_actions, but each action is given as its full hop path
+    pub fn expand_with_paths(&self) -> (Vec<Game<'a>>, Vec<Vec<Position>>) {
+        let mut next_states = vec![];
+        let mut paths = vec![];
+
+        let mut moves = MoveCursor::new(self, MoveOrder::Natural);
+        while let Some(path) = moves.next_path(self) {
+            next_states.push(self.move_to(path[0], path[path.len() - 1]));
+            paths.push(path);
+        }
+
+        (next_states, paths)
+    }
+
+    pub fn expand(&self, record_actions: bool) -> (Vec<Game<'a>>, Vec<Action>) {
+        self.expand_actions(self.actions(), record_actions)
+    }
+
+    pub fn expand_forward_only(&self, record_actions: bool) -> (Vec<Game<'a>>, Vec<Action>) {
+        self.expand_actions(self.actions_forward_only(), record_actions)
+    }
+
+    fn expand_actions(&self, actions: Vec<Action>, record_actions: bool) -> (Vec<Game<'a>>, Vec<Action>) {
+        let next_states = actions.iter().map(|&Action(from, to)| self.move_to(from, to)).collect();
+        (next_states, if record_actions { actions } else { vec![] })
+    }
+
+    // the pieces are not checked, see place_piece and the other editing methods for checked changes
     pub fn clone_with_pieces(&self, pieces: &[Position]) -> Self {
-        Self { pieces: pieces.to_vec(), ..self.clone() }
+        Self { pieces: pieces.to_vec(), ..self.clone() }.refreshed()
     }
 
+    // the parent of each position reachable by the piece, the piece itself at its own position and INVALID_POSITION elsewhere.
+    // Follow the parents with path_to to get the hops of a move.
     pub fn possible_moves_with_path(&self, piece: Position) -> Vec<Position> {
         let mut result = vec![INVALID_POSITION; self.board.board_size];
-        let mut queue = vec![piece];
+        let mut queue = VecDeque::from([(piece, 0)]); // positions to hop from, with the number of hops that reached them
 
         result[piece as usize] = piece;
 
-        while let Some(pos) = queue.pop() {
+        let occupied = self.occupancy.without(piece); // the moving piece has left its cell
+
+        // Without a hop limit the positions are visited last in first out, which keeps the hop paths of the original rules.
+        // With a limit they are visited breadth first, so every position is first reached by the fewest hops and the limit
+        // does not hide any destination.
+        let limited = self.ruleset.max_hops.is_some();
+        while let Some((pos, hops)) = if limited { queue.pop_front() } else { queue.pop_back() } {
+            if self.ruleset.max_hops.is_some_and(|max_hops| hops >= max_hops) {
+                continue
+            }
+
             for direction in 0..6 {
-                let mut cp = pos;
-                let mut steps = 0; // the distance to pos when hopping not started, or the steps remaing when hopping started
-                let mut hopping_started = false;
+                if (self.board.ray_masks[pos as usize][direction] & occupied).is_empty() { // nothing to hop over
+                    continue
+                }
 
-                loop {
-                    cp = self.board.ajd_matrix[cp as usize][direction];
-                    if cp == INVALID_POSITION {
-                        break
-                    }
+                // hop over the nearest piece at `steps` cells away, landing the same distance behind it
+                let ray = &self.board.rays[pos as usize][direction];
+                let steps = ray.iter().position(|&cp| occupied.contains(cp)).unwrap();
+                if self.ruleset.max_hop_distance.is_some_and(|max_distance| steps > max_distance) || ray.len() <= 2 * steps + 1 {
+                    continue
+                }
+                if ray[steps + 1..=2 * steps + 1].iter().any(|&cp| occupied.contains(cp)) { // encounter obstacle
+                    continue
+                }
 
-                    match (cp != piece && self.has_piece(cp), hopping_started, steps) {
-                        (true, true, _) => break, // encounter obstacle, stop
-                        (true, false, _) => hopping_started = true, // start hopping
-                        (false, true, 0) => { // hopping succeed
-                            if result[cp as usize] != INVALID_POSITION { // can be reached by another (shorter) path
-                                break
-                            }
-                            queue.push(cp);
-                            result[cp as usize] = pos;
-                            break
-                        }
-                        (false, true, _) => steps -= 1, // hopping continue
-                        (false, false, _) => steps += 1, // continue to move
-                    }
+                let landing = ray[2 * steps + 1];
+                if result[landing as usize] != INVALID_POSITION { // can be reached by another path
+                    continue
                 }
+                queue.push_back((landing, hops + 1));
+                result[landing as usize] = pos;
             }
         }
 
@@ -175,29 +691,609 @@ impl Game {
 
     // higher is better for p1
     pub fn heuristic(&self) -> f64 {
-        let mut p1_dist = self.p1_distance();
-        if p1_dist <= self.board.min_distance {
+        let mut p1_dist = self.adjusted_distance(0);
+        if self.is_finished(0) {
             p1_dist = 0 // enlarge the wining gap
         }
 
-        let mut p2_dist = self.p2_distance();
-        if p2_dist <= self.board.min_distance {
+        let mut p2_dist = self.adjusted_distance(1);
+        if self.is_finished(1) {
             p2_dist = 0
         }
 
         p2_dist as f64 - p1_dist as f64
     }
 
+    // the heuristic seen by `player` against their closest opponent, so heuristic_for(0) is heuristic in two-player games
+    pub fn heuristic_for(&self, player: usize) -> f64 {
+        let dist = |p| if self.is_finished(p) { 0 } else { self.adjusted_distance(p) };
+        let closest = (0..self.board.n_players).filter(|&p| p != player).map(dist).min().unwrap();
+        closest as f64 - dist(player) as f64
+    }
+
+    // the same position seen through a symmetry of the board. The round number is kept and the seat of the player to move is rotated.
+    pub fn transformed(&self, symmetry: &Symmetry) -> Game<'a> {
+        let n_players = self.board.n_players;
+        let preimage = |image: usize| (image + n_players - symmetry.player_shift) % n_players;
+        let transform = |pieces: &[Position]| {
+            let mut result: Vec<_> = pieces.iter().map(|&piece| symmetry.apply(piece)).collect();
+            result.sort_unstable();
+            result
+        };
+        let pieces = (0..n_players).flat_map(|image| transform(self.pieces_slice(preimage(image)))).collect();
+        let setup = self.setup.as_ref().map(|setup| Setup::new(self.board, (0..n_players).map(|image| transform(&setup.pieces[preimage(image)])).collect()));
+
+        let seat = self.turn % n_players;
+        let turn = self.turn - seat + (seat + symmetry.player_shift) % n_players;
+        let finished = self.finished.iter().map(|&player| (player + symmetry.player_shift) % n_players).collect();
+        Game { board: self.board, turn, pieces, finished, ruleset: self.ruleset, history: vec![], setup, distances: vec![], occupancy: Bitboard::EMPTY, player_masks: vec![], pieces_hash: 0 }.refreshed() // the history is not transformed
+    }
+
+    // the smallest key among all symmetric positions, and the index of the symmetry in board.symmetries that produced it.
+    // Use the inverse of that symmetry to map positions and players of the canonical key back to this game.
+    // Symmetries that swap players with different numbers of pieces are skipped, as their keys have another layout.
+    pub fn canonical_key(&self) -> (Vec<u8>, usize) {
+        let n_players = self.board.n_players;
+        self.board.symmetries.iter().enumerate()
+            .filter(|(_, symmetry)| (0..n_players).all(|player| self.pieces_range(player).len() == self.pieces_range((player + symmetry.player_shift) % n_players).len()))
+            .map(|(i, symmetry)| (self.transformed(symmetry).key(), i)).min().unwrap()
+    }
+
+    // the board fingerprint, mixed with the numbers of pieces if a setup changed them, so keys of different layouts do not mix
+    fn key_fingerprint(&self) -> u32 {
+        match &self.setup {
+            Some(setup) if setup.pieces.iter().any(|pieces| pieces.len() != self.board.n_pieces) => fnv1a(self.board.fingerprint, setup.pieces.iter().map(|pieces| pieces.len() as u8)),
+            _ => self.board.fingerprint,
+        }
+    }
+
     pub fn key(&self) -> Vec<u8> {
-        let mut result = vec![self.turn as u8];
+        let mut result = vec![KEY_VERSION];
+        result.extend_from_slice(&self.key_fingerprint().to_le_bytes());
+        result.extend_from_slice(&(self.turn as u32).to_le_bytes());
         result.extend_from_slice(&self.pieces);
         result
     }
 
-    pub fn from_key(proto: &Game, key: &[u8]) -> Game {
-        let mut game = Game::new(&proto.board);
-        game.turn = key[0] as _;
-        game.pieces = key[1..].to_vec();
+    // the key_hash of a valid key of a game with the same board and numbers of pieces as this one
+    pub fn key_hash_of(&self, key: &[u8]) -> u64 {
+        let pieces = &key[KEY_HEADER_SIZE..];
+        (0..self.board.n_players).flat_map(|player| pieces[self.pieces_range(player)].iter().map(move |&piece| (player, piece)))
+            .fold(zobrist_turn(key_turn(key)), |hash, (player, piece)| hash ^ self.board.zobrist_pieces[player][piece as usize])
+    }
+
+    // the game of a key on the same board as `proto`, which also provides the ruleset and the setup
+    pub fn from_key(proto: &Game<'a>, key: &[u8]) -> Result<Game<'a>, KeyError> {
+        let board = proto.board;
+        let expected = KEY_HEADER_SIZE + proto.pieces.len();
+        if key.len() < KEY_HEADER_SIZE {
+            return Err(KeyError::Length { expected, actual: key.len() })
+        }
+        if key[0] != KEY_VERSION {
+            return Err(KeyError::Version { version: key[0] })
+        }
+        if key.len() != expected {
+            return Err(KeyError::Length { expected, actual: key.len() })
+        }
+        let fingerprint = u32::from_le_bytes(key[1..5].try_into().unwrap());
+        if fingerprint != proto.key_fingerprint() {
+            return Err(KeyError::Board { expected: proto.key_fingerprint(), actual: fingerprint })
+        }
+
+        let pieces = &key[KEY_HEADER_SIZE..];
+        if let Some(&pos) = pieces.iter().find(|&&pos| pos as usize >= board.board_size) {
+            return Err(KeyError::PositionOutOfBoard { pos })
+        }
+        if let Some(player) = (0..board.n_players).find(|&player| !pieces[proto.pieces_range(player)].is_sorted()) {
+            return Err(KeyError::Unsorted { player })
+        }
+        let mut occupied = Bitboard::EMPTY;
+        for &pos in pieces {
+            if occupied.contains(pos) {
+                return Err(KeyError::DuplicatedPosition { pos })
+            }
+            occupied.insert(pos);
+        }
+
+        Ok(Game::at_position(board, proto.ruleset, proto.setup.clone(), key_turn(key) as _, pieces.to_vec()))
+    }
+
+    // a game without history at the given position. The finishing order is lost, so finished players are recorded in seat order.
+    fn at_position(board: &'a Board, ruleset: Ruleset, setup: Option<Setup>, turn: usize, pieces: Vec<Position>) -> Game<'a> {
+        let mut game = Game { board, turn, pieces, finished: vec![], ruleset, history: vec![], setup, distances: vec![], occupancy: Bitboard::EMPTY, player_masks: vec![], pieces_hash: 0 }.refreshed();
+        game.finished = (0..board.n_players).filter(|&player| game.is_finished(player)).collect();
         game
     }
+
+    // replaces the position with edited pieces of each player. Unlike moves, edits are not recorded, and the history is cleared.
+    // If the numbers of pieces change, the game continues as if it started from the edited position (see Setup).
+    fn edit(&mut self, mut pieces: Vec<Vec<Position>>) {
+        let n_players = self.board.n_players;
+        pieces.iter_mut().for_each(|pieces| pieces.sort_unstable());
+        let setup = if (0..n_players).all(|player| pieces[player].len() == self.pieces_range(player).len()) {
+            self.setup.take()
+        } else {
+            Some(Setup::new(self.board, pieces.clone()))
+        };
+        *self = Game::at_position(self.board, self.ruleset, setup, self.turn, pieces.concat());
+    }
+
+    fn edited_pieces(&self) -> Vec<Vec<Position>> {
+        (0..self.board.n_players).map(|player| self.pieces_slice(player).to_vec()).collect()
+    }
+
+    fn check_empty(&self, pos: Position) -> Result<(), EditError> {
+        if pos as usize >= self.board.board_size {
+            return Err(EditError::OutOfBoard { pos })
+        }
+        if self.has_piece(pos) {
+            return Err(EditError::Occupied { pos })
+        }
+        Ok(())
+    }
+
+    // the owner of the piece at `pos`
+    fn check_piece(&self, pos: Position) -> Result<usize, EditError> {
+        if pos as usize >= self.board.board_size {
+            return Err(EditError::OutOfBoard { pos })
+        }
+        (0..self.board.n_players).find(|&player| self.player_masks[player].contains(pos)).ok_or(EditError::NoPiece { pos })
+    }
+
+    // adds a piece of `player` at `pos`
+    pub fn place_piece(&mut self, player: usize, pos: Position) -> Result<(), EditError> {
+        if player >= self.board.n_players {
+            return Err(EditError::NoPlayer { player })
+        }
+        self.check_empty(pos)?;
+        let mut pieces = self.edited_pieces();
+        pieces[player].push(pos);
+        self.edit(pieces);
+        Ok(())
+    }
+
+    pub fn remove_piece(&mut self, pos: Position) -> Result<(), EditError> {
+        let player = self.check_piece(pos)?;
+        if self.pieces_slice(player).len() == 1 {
+            return Err(EditError::LastPiece { player })
+        }
+        let mut pieces = self.edited_pieces();
+        pieces[player].retain(|&piece| piece != pos);
+        self.edit(pieces);
+        Ok(())
+    }
+
+    // moves a piece anywhere, regardless of the rules
+    pub fn move_piece(&mut self, from: Position, to: Position) -> Result<(), EditError> {
+        let player = self.check_piece(from)?;
+        self.check_empty(to)?;
+        let mut pieces = self.edited_pieces();
+        pieces[player].iter_mut().filter(|piece| **piece == from).for_each(|piece| *piece = to);
+        self.edit(pieces);
+        Ok(())
+    }
+
+    // keeps the round and changes the seat of the turn, so the turn may go back
+    pub fn set_player_to_move(&mut self, player: usize) -> Result<(), EditError> {
+        if player >= self.board.n_players {
+            return Err(EditError::NoPlayer { player })
+        }
+        if self.finished.contains(&player) && self.board.n_players > 2 {
+            return Err(EditError::Finished { player })
+        }
+        self.turn = self.turn - self.player_to_move() + player;
+        self.history.clear();
+        Ok(())
+    }
+
+    // checks some necessary conditions for the position to occur in a game that started from the bases.
+    // A position that passes may still be unreachable, but an edited position that fails cannot be played to.
+    pub fn check_reachable(&self) -> Result<(), Unreachable> {
+        let n_players = self.board.n_players;
+        for player in 0..n_players {
+            let pieces = self.pieces_slice(player);
+            if pieces.len() != self.board.n_pieces {
+                return Err(Unreachable::PieceCount { player, expected: self.board.n_pieces, actual: pieces.len() })
+            }
+
+            // each move takes at most one piece out of the base. Finished players skip their turns, so this is an upper bound
+            let moves = (self.turn + n_players - 1 - player) / n_players;
+            let moved = pieces.iter().filter(|piece| !self.board.bases[player].contains(piece)).count();
+            if moved > moves {
+                return Err(Unreachable::TooManyMoved { player, moved, moves })
+            }
+        }
+
+        if n_players == 2 && (0..n_players).all(|player| self.is_finished(player)) {
+            return Err(Unreachable::SeveralFinished)
+        }
+        Ok(())
+    }
+
+    // whether the game is over at this position, see status
+    pub fn is_terminal(&self) -> bool {
+        self.status() != Status::Ongoing
+    }
+}
+
+// follows the parent array from `to` back to `from`
+pub(crate) fn path_to(parents: &[Position], from: Position, to: Position) -> Vec<Position> {
+    let mut path = vec![to];
+    let mut pos = to;
+    while pos != from {
+        pos = parents[pos as usize];
+        assert!(pos != INVALID_POSITION, "no path from {from} to {to}");
+        path.push(pos);
+    }
+    path.reverse();
+    path
+}
+
+// the positions of a game where the player to move always takes the move that gains the most distance, until it ends.
+// The searches meet no repetitions along it, so tests of several modules use it.
+#[cfg(test)]
+pub(crate) fn raced_positions(board: &Board, max_moves: usize) -> Vec<Game<'_>> {
+    let mut game = Game::new(board);
+    let mut result = vec![game.clone()];
+    for _ in 0..max_moves {
+        let distance_map = &board.distance_maps[game.player_to_move()];
+        let Some(Action(from, to)) = game.actions().into_iter().max_by_key(|&Action(from, to)| distance_map[from as usize] as i64 - distance_map[to as usize] as i64) else {
+            break
+        };
+        game.make_move(from, to);
+        result.push(game.clone());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::builtin_board;
+
+    // the positions of a game where the player to move always takes the i-th move of a fixed sequence
+    fn positions(board: &Board, n_moves: usize) -> Vec<Game<'_>> {
+        let mut game = Game::new(board);
+        let mut result = vec![game.clone()];
+        for i in 0..n_moves {
+            let actions = game.actions();
+            if actions.is_empty() {
+                break
+            }
+            let Action(from, to) = actions[(i * 7 + 3) % actions.len()];
+            game.make_move(from, to);
+            result.push(game.clone());
+        }
+        result
+    }
+
+    // every field but the board, ruleset and setup, which moves do not change
+    fn state(game: &Game) -> impl PartialEq + std::fmt::Debug {
+        (game.pieces.clone(), game.turn, game.finished.clone(), game.history.clone(), game.distances.clone(), game.occupancy, game.player_masks.clone(), game.pieces_hash)
+    }
+
+    #[test]
+    fn make_move_and_unmake_move_round_trip() {
+        let mut finishes = 0;
+        for (rank, plus, n_players) in [(3, false, 2), (2, false, 3), (2, false, 6)] {
+            let board = builtin_board(rank, plus, n_players).unwrap();
+            for mut game in positions(board, 40).into_iter().chain(raced_positions(board, 400)) {
+                let before = state(&game);
+                for Action(from, to) in game.actions() {
+                    let undo = game.make_move(from, to);
+                    assert_eq!(state(&game), state(&game.clone().refreshed()), "the caches differ from the recomputed ones");
+                    finishes += (game.finished.len() > undo.n_finished) as usize;
+                    game.unmake_move(undo);
+                    assert_eq!(state(&game), before, "{} with {n_players} players", board.name);
+                }
+            }
+        }
+        assert!(finishes > 0, "no move finished a player");
+    }
+
+    #[test]
+    fn canonical_keys_are_invariant_under_symmetries() {
+        for (rank, plus, n_players) in [(3, false, 2), (4, true, 2), (3, false, 3), (3, true, 3), (2, false, 6)] {
+            let board = builtin_board(rank, plus, n_players).unwrap();
+            assert!(board.symmetries.len() > 1);
+            for game in positions(board, 40) {
+                let (key, _) = game.canonical_key();
+                for symmetry in &board.symmetries {
+                    let transformed = game.transformed(symmetry);
+                    assert_eq!(transformed.canonical_key().0, key, "{} with {n_players} players", board.name);
+                    assert_eq!(transformed.transformed(&symmetry.inverse(n_players)).key(), game.key(), "{} with {n_players} players", board.name);
+                }
+            }
+        }
+    }
+
+    // the original generator, which walks the cells of each direction instead of using the rays of the board.
+    // Without a hop limit it visits the hops last in first out, like possible_moves_with_path.
+    fn walked_moves_with_path(game: &Game, piece: Position) -> Vec<Position> {
+        let mut result = vec![INVALID_POSITION; game.board.board_size];
+        let mut queue = VecDeque::from([(piece, 0)]);
+        result[piece as usize] = piece;
+
+        while let Some((pos, hops)) = if game.ruleset.max_hops.is_some() { queue.pop_front() } else { queue.pop_back() } {
+            if game.ruleset.max_hops.is_some_and(|max_hops| hops >= max_hops) {
+                continue
+            }
+            for direction in 0..6 {
+                let mut cp = pos;
+                let mut steps = 0;
+                let mut hopping_started = false;
+
+                loop {
+                    cp = game.board.ajd_matrix[cp as usize][direction];
+                    if cp == INVALID_POSITION {
+                        break
+                    }
+
+                    match (cp != piece && game.has_piece(cp), hopping_started, steps) {
+                        (true, true, _) => break,
+                        (true, false, _) => hopping_started = true,
+                        (false, true, 0) => {
+                            if result[cp as usize] == INVALID_POSITION {
+                                queue.push_back((cp, hops + 1));
+                                result[cp as usize] = pos;
+                            }
+                            break
+                        }
+                        (false, true, _) => steps -= 1,
+                        (false, false, _) => {
+                            steps += 1;
+                            if game.ruleset.max_hop_distance.is_some_and(|max_distance| steps > max_distance) {
+                                break
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for &next in &game.board.ajd_matrix[piece as usize] {
+            if next != INVALID_POSITION && !game.has_piece(next) {
+                result[next as usize] = piece;
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn hop_paths_match_the_walked_generator() {
+        let rulesets = [Ruleset::LONG, Ruleset::SHORT, Ruleset { max_hop_distance: Some(2), max_hops: Some(2) }, Ruleset { max_hop_distance: None, max_hops: Some(1) }];
+        for (rank, plus, n_players) in [(4, false, 2), (5, true, 2), (3, false, 3)] {
+            let board = builtin_board(rank, plus, n_players).unwrap();
+            for position in positions(board, 60) {
+                for ruleset in rulesets {
+                    let game = Game { ruleset, ..position.clone() };
+                    for &piece in &game.pieces {
+                        assert_eq!(game.possible_moves_with_path(piece), walked_moves_with_path(&game, piece), "{} {ruleset:?}", board.name);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn moves_match_the_walked_generator() {
+        for (rank, plus, n_players) in [(4, false, 2), (3, true, 2), (3, false, 4)] {
+            let board = builtin_board(rank, plus, n_players).unwrap();
+            for game in positions(board, 60).into_iter().chain(raced_positions(board, 400)) {
+                let walked: Vec<_> = if game.is_ended() { vec![] } else {
+                    game.pieces_slice(game.player_to_move()).iter().flat_map(|&piece| {
+                        let parents = walked_moves_with_path(&game, piece);
+                        (0..board.board_size).filter(move |&dest| parents[dest] != INVALID_POSITION && dest != piece as usize).map(move |dest| Action(piece, dest as Position))
+                    }).collect()
+                };
+                assert_eq!(game.actions(), walked, "{}", board.name);
+            }
+        }
+    }
+
+    #[test]
+    fn try_move_rejects_illegal_moves() {
+        let board = builtin_board(3, false, 2).unwrap();
+        let game = Game::new(board);
+        let before = state(&game);
+        let (own, other) = (game.pieces_slice(0)[0], game.pieces_slice(1)[0]);
+        let empty = (0..board.board_size as Position).find(|&pos| !game.has_piece(pos)).unwrap();
+        let far = (0..board.board_size as Position).find(|&pos| !game.has_piece(pos) && game.actions().iter().all(|&Action(_, to)| to != pos)).unwrap();
+
+        assert_eq!(game.try_move(own, board.board_size as Position).err(), Some(MoveError::OutOfBoard { pos: board.board_size as Position }));
+        assert_eq!(game.try_move(INVALID_POSITION, empty).err(), Some(MoveError::OutOfBoard { pos: INVALID_POSITION }));
+        assert_eq!(game.try_move(other, empty).err(), Some(MoveError::NotOwnPiece { player: 0, from: other }));
+        assert_eq!(game.try_move(empty, far).err(), Some(MoveError::NotOwnPiece { player: 0, from: empty }));
+        assert_eq!(game.try_move(own, own).err(), Some(MoveError::Unreachable { from: own, to: own }));
+        assert_eq!(game.try_move(own, far).err(), Some(MoveError::Unreachable { from: own, to: far }));
+        assert_eq!(state(&game), before);
+
+        for Action(from, to) in game.actions() {
+            assert_eq!(state(&game.try_move(from, to).unwrap()), state(&game.move_to(from, to)));
+        }
+        assert_eq!(state(&game), before);
+
+        let game = raced_positions(board, 400).pop().unwrap();
+        assert!(game.is_ended());
+        assert_eq!(game.try_move(game.pieces_slice(game.player_to_move())[0], empty).err(), Some(MoveError::GameEnded));
+    }
+
+    #[test]
+    fn keys_round_trip_and_check_their_header() {
+        let board = builtin_board(3, false, 2).unwrap();
+        for game in positions(board, 40) {
+            let key = game.key();
+            assert_eq!((key[0], key_turn(&key) as usize, key.len()), (KEY_VERSION, game.turn, KEY_HEADER_SIZE + game.pieces.len()));
+            let loaded = Game::from_key(&game, &key).unwrap();
+            assert_eq!((&loaded.pieces, loaded.turn, loaded.key_hash()), (&game.pieces, game.turn, game.key_hash()));
+            assert_eq!(game.key_hash_of(&key), game.key_hash());
+        }
+
+        let game = Game::new(board);
+        let key = game.key();
+        let other = Game::new(builtin_board(2, true, 2).unwrap());
+        assert_eq!(other.pieces.len(), game.pieces.len());
+        assert_eq!(Game::from_key(&other, &key).err(), Some(KeyError::Board { expected: board_fingerprint(&other), actual: board_fingerprint(&game) }));
+
+        let mut wrong = key.clone();
+        wrong[1] ^= 1;
+        assert_eq!(Game::from_key(&game, &wrong).err(), Some(KeyError::Board { expected: board_fingerprint(&game), actual: board_fingerprint(&game) ^ 1 }));
+        wrong = key.clone();
+        wrong[0] = KEY_VERSION + 1;
+        assert_eq!(Game::from_key(&game, &wrong).err(), Some(KeyError::Version { version: KEY_VERSION + 1 }));
+        assert_eq!(Game::from_key(&game, &key[..key.len() - 1]).err(), Some(KeyError::Length { expected: key.len(), actual: key.len() - 1 }));
+
+        // a setup with fewer pieces has keys of another layout, so the keys of the full game are rejected
+        let pieces = vec![board.bases[0][1..].to_vec(), board.bases[1].clone()];
+        let handicapped = Game::with_setup(board, Ruleset::default(), pieces).unwrap();
+        assert_ne!(board_fingerprint(&handicapped), board_fingerprint(&game));
+        assert!(matches!(Game::from_key(&handicapped, &key), Err(KeyError::Length { .. })));
+        let handicapped_key = handicapped.key();
+        assert_eq!(Game::from_key(&handicapped, &handicapped_key).unwrap().pieces, handicapped.pieces);
+    }
+
+    fn board_fingerprint(game: &Game) -> u32 {
+        u32::from_le_bytes(game.key()[1..5].try_into().unwrap())
+    }
+
+    #[test]
+    fn edits_keep_the_pieces_sorted() {
+        let board = builtin_board(4, false, 3).unwrap();
+        let mut game = Game::new(board);
+        let sorted = |game: &Game| (0..board.n_players).all(|player| game.pieces_slice(player).is_sorted());
+        let empty = |game: &Game, i: usize| (0..board.board_size as Position).filter(|&pos| !game.has_piece(pos)).nth(i).unwrap();
+
+        for i in 0..30 {
+            let player = i / 3 % board.n_players;
+            let pieces = game.pieces_slice(player).to_vec();
+            match i % 3 {
+                0 => game.place_piece(player, empty(&game, i * 13 % 50)).unwrap(),
+                1 => game.move_piece(pieces[i % pieces.len()], empty(&game, i * 7 % 50)).unwrap(),
+                _ => game.remove_piece(pieces[pieces.len() / 2]).unwrap(),
+            }
+            assert!(sorted(&game), "after edit {i}: {:?}", game.pieces);
+            assert_eq!(state(&game), state(&game.clone().refreshed()));
+
+            // moves after the edit keep them sorted too
+            if let Some(Action(from, to)) = game.actions().last().copied() {
+                let moved = game.move_to(from, to);
+                assert!(sorted(&moved), "after a move from edit {i}: {:?}", moved.pieces);
+            }
+        }
+
+        let pos = game.pieces_slice(0)[0];
+        let before = state(&game);
+        assert_eq!(game.place_piece(1, pos), Err(EditError::Occupied { pos }));
+        assert_eq!(game.move_piece(pos, board.board_size as Position), Err(EditError::OutOfBoard { pos: board.board_size as Position }));
+        assert_eq!(state(&game), before);
+    }
+
+    #[test]
+    fn moving_back_and_forth_repeats_the_position() {
+        let board = builtin_board(3, false, 2).unwrap();
+        let mut game = Game::new(board);
+        // a single step of each player, so both can step back
+        let step = |game: &Game| game.actions().into_iter().find(|&Action(from, to)| board.ajd_matrix[from as usize].contains(&to)).unwrap();
+        let Action(a, b) = step(&game);
+        let Action(c, d) = step(&game.move_to(a, b));
+        let cycle = [(a, b), (c, d), (b, a), (d, c)];
+
+        let mut undos = vec![];
+        for (round, expected) in [(0, 1), (1, 2)] {
+            for (i, &(from, to)) in cycle.iter().enumerate() {
+                assert_eq!(game.status(), Status::Ongoing, "round {round} move {i}");
+                assert_eq!(game.try_move(from, to).err(), None);
+                undos.push(game.make_move(from, to));
+            }
+            assert_eq!(game.repetitions(), expected);
+        }
+        assert!(game.is_threefold_repetition());
+        assert_eq!(game.status(), Status::Ended { winner: None, reason: EndReason::Repetition });
+        assert!(game.actions().is_empty());
+
+        let n_history = game.history.len();
+        game.unmake_move(undos.pop().unwrap());
+        assert_eq!(game.history.len(), n_history - 1);
+        assert_eq!(game.status(), Status::Ongoing);
+        for undo in undos.drain(4..).rev() {
+            game.unmake_move(undo);
+        }
+        assert_eq!((game.repetitions(), game.history.len()), (1, 4));
+        assert_eq!(game.status(), Status::Ongoing);
+    }
+
+    #[test]
+    fn setups_from_json_add_and_remove_pieces() {
+        let board = builtin_board(4, false, 2).unwrap();
+        let (base, other) = (&board.bases[0], &board.bases[1]);
+        let free = (0..board.board_size as Position).find(|&pos| !base.contains(&pos) && !other.contains(&pos)).unwrap();
+
+        let json = serde_json::json!([{"base": "plus"}, {"remove": [other[0], other[1]], "add": [free]}]);
+        let pieces = Setup::pieces_from_json(board, &json).unwrap();
+        assert_eq!(pieces[0], board.plus_base(0));
+        assert_eq!(pieces[1], [&other[2..], &[free]].concat());
+
+        let json = serde_json::json!([base[1..], {"base": "standard"}]);
+        assert_eq!(Setup::pieces_from_json(board, &json).unwrap(), vec![base[1..].to_vec(), other.clone()]);
+        assert_eq!(Setup::pieces_from_json(board, &serde_json::json!([{}, {}])).unwrap(), board.bases);
+
+        for json in [
+            serde_json::json!({"base": "plus"}),
+            serde_json::json!([{"base": "huge"}, {}]),
+            serde_json::json!([{"remove": [free]}, {}]),
+            serde_json::json!([{"add": free}, {}]),
+            serde_json::json!([[0, 255], {}]),
+            serde_json::json!([[-1], {}]),
+        ] {
+            assert!(matches!(Setup::pieces_from_json(board, &json), Err(SetupError::Definition(_))), "{json}");
+        }
+    }
+
+    #[test]
+    fn setups_are_checked_before_the_game_starts() {
+        let board = builtin_board(4, false, 2).unwrap();
+        let (base, other) = (&board.bases[0], &board.bases[1]);
+        let with_setup = |pieces: Vec<Vec<Position>>| Game::with_setup(board, Ruleset::default(), pieces).err().map(|e| e.to_string());
+        let error = |e: SetupError| Some(e.to_string());
+
+        assert_eq!(with_setup(vec![base.clone()]), error(SetupError::PlayerCount { expected: 2, actual: 1 }));
+        assert_eq!(with_setup(vec![base.clone(), vec![]]), error(SetupError::NoPieces { player: 1 }));
+        let outside = board.board_size as Position;
+        assert_eq!(with_setup(vec![[&base[..], &[outside]].concat(), other.clone()]), error(SetupError::PositionOutOfBoard { player: 0, pos: outside }));
+        assert_eq!(with_setup(vec![base.clone(), [&other[..], &[base[3]]].concat()]), error(SetupError::Overlap { pos: base[3] }));
+
+        // a single piece of player 2 is already in their target, which is player 1's base
+        assert!(board.target_masks[1].contains(base[0]));
+        assert_eq!(with_setup(vec![base[1..].to_vec(), vec![base[0]]]), error(SetupError::AlreadyFinished { player: 1 }));
+
+        let game = Game::with_setup(board, Ruleset::default(), vec![base.iter().rev().copied().collect(), other.clone()]).unwrap();
+        assert_eq!(game.pieces, Game::new(board).pieces);
+        assert_eq!(game.setup.as_ref().unwrap().pieces, board.bases);
+    }
+
+    #[test]
+    fn handicaps_compare_the_setup_with_the_base() {
+        let board = builtin_board(4, false, 2).unwrap();
+        let (base, other) = (&board.bases[0], &board.bases[1]);
+        let start = Game::new(board);
+        let distance_map = &board.distance_maps[0];
+
+        // one piece of player 1 is moved forward
+        let Action(from, to) = start.actions_forward_only()[0];
+        let game = start.try_move(from, to).unwrap();
+        let moved = Game::with_setup(board, Ruleset::default(), vec![game.pieces_slice(0).to_vec(), other.clone()]).unwrap();
+        let setup = moved.setup.as_ref().unwrap();
+        assert_eq!(setup.handicap(board, 0), Handicap { extra_pieces: 0, head_start: distance_map[from as usize] as i64 - distance_map[to as usize] as i64 });
+        assert_eq!(setup.handicap(board, 1), Handicap { extra_pieces: 0, head_start: 0 });
+
+        // the head start is what the adjusted distance is shorter than from the base
+        for pieces in [vec![board.plus_base(0), other.clone()], vec![base[1..].to_vec(), other[..7].to_vec()]] {
+            let game = Game::with_setup(board, Ruleset::default(), pieces.clone()).unwrap();
+            for (player, pieces) in pieces.iter().enumerate() {
+                let handicap = game.setup.as_ref().unwrap().handicap(board, player);
+                assert_eq!(handicap.extra_pieces, pieces.len() as isize - board.n_pieces as isize);
+                assert_eq!(handicap.head_start, start.adjusted_distance(player) as i64 - game.adjusted_distance(player) as i64);
+            }
+        }
+    }
 }
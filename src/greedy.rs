@@ -1,42 +1,43 @@
-use std::collections::BTreeMap;
+use crate::{cache::ScoreCache, game::{Game, Action}, softmax, sample_categorical, lookup_score};
 
-use crate::{game::{Game, Action}, softmax, sample_categorical};
+pub fn greedy<'a>(game: &Game<'a>, temp: f64) -> (Game<'a>, Action) {
+    let mut actions = game.actions();
 
-pub fn greedy(game: &Game, temp: f64) -> (Game, Action) {
-    let (mut next_states, mut actions) = game.expand(true);
-
-    if next_states.is_empty() {
+    if actions.is_empty() {
         panic!("Game already ends!")
     }
 
-    let mut values: Vec<_> = next_states.iter().map(|g| g.heuristic()).collect();
-    if game.is_p2_moving_next() {
-        values.iter_mut().for_each(|x| *x = -*x);
-    }
+    // repeated positions are draws
+    let mut state = game.clone();
+    let player = game.player_to_move();
+    let mut values: Vec<_> = actions.iter().map(|&action| state.with_move(action, |g| if g.repetitions() > 0 { 0. } else { g.heuristic_for(player) })).collect();
     softmax(&mut values, temp);
 
     let i = sample_categorical(values.into_iter());
 
-    (next_states.swap_remove(i), actions.swap_remove(i))
+    let action = actions.swap_remove(i);
+    (game.move_to(action.0, action.1), action)
 }
 
-pub fn greedy_poll(game: &Game, temp: f64, forward_only: bool, score_map: &BTreeMap<Vec<u8>, f64>) -> Result<(Game, Action), Vec<Vec<u8>>> {
-    let (mut next_states, mut actions) = if forward_only {
-        game.expand_forward_only(true)
+pub fn greedy_poll<'a>(game: &Game<'a>, temp: f64, forward_only: bool, canonical: bool, score_map: &ScoreCache) -> Result<(Game<'a>, Action), Vec<Vec<u8>>> {
+    let mut actions = if forward_only {
+        game.actions_forward_only()
     } else {
-        game.expand(true)
+        game.actions()
     };
 
-    if next_states.is_empty() {
+    if actions.is_empty() {
         panic!("Game already ends!")
     }
 
-    let no_values: Vec<_> = next_states.iter().map(|g| g.key()).filter(|k| !score_map.contains_key(k)).collect();
+    let mut state = game.clone();
+    let scores: Vec<_> = actions.iter().map(|&action| state.with_move(action, |g| if g.repetitions() > 0 { Ok(0.5) } else { lookup_score(g, canonical, score_map) })).collect();
+    let no_values: Vec<_> = scores.iter().filter_map(|score| score.clone().err()).collect();
     if !no_values.is_empty() {
         return Err(no_values)
     }
 
-    let mut values: Vec<_> = next_states.iter().map(|g| score_map[&g.key()]).collect();
+    let mut values: Vec<_> = scores.into_iter().map(|score| score.unwrap()).collect();
     if game.is_p2_moving_next() {
         values.iter_mut().for_each(|x| *x = 1. - *x);
     }
@@ -44,7 +45,6 @@ pub fn greedy_poll(game: &Game, temp: f64, forward_only: bool, score_map: &BTree
 
     let i = sample_categorical(values.into_iter());
 
-    Ok((next_states.swap_remove(i), actions.swap_remove(i)))
+    let action = actions.swap_remove(i);
+    Ok((game.move_to(action.0, action.1), action))
 }
-
-
@@ -1,7 +1,4 @@
 #![allow(clippy::missing_safety_doc)]
-#![feature(vec_into_raw_parts)]
-
-use std::collections::BTreeMap;
 
 use serde_json::{json, Value as JsonValue};
 
@@ -12,6 +9,13 @@ pub static INVALID_POSITION: Position = Position::MAX;
 
 static mut RANDOM: u32 = 39393;
 
+static mut CANONICAL_KEYS: bool = false; // whether the *_poll caches are keyed by canonical keys
+
+static mut CHECK_COLLISIONS: bool = false; // whether the *_poll caches keep the keys to detect hash collisions
+
+static mut TRANSPOSITION_TABLE_SIZE: usize = 1 << 16; // the number of entries of the transposition table of each alphabeta search
+static mut MOVE_ORDERING: bool = true; // whether alphabeta searches order the moves, see alphabeta::MoveOrdering
+
 fn get_random_number() -> u32 {
     unsafe {
         RANDOM ^= RANDOM << 13;
@@ -22,7 +26,7 @@ fn get_random_number() -> u32 {
 }
 
 fn get_random_float() -> f64 {
-    get_random_number() as f64 / core::u32::MAX as f64
+    get_random_number() as f64 / u32::MAX as f64
 }
 
 fn random_shuffle<T>(x: &mut [T]) {
@@ -39,6 +43,22 @@ fn softmax(x: &mut [f64], temp: f64) {
     x.iter_mut().for_each(|v| *v = (*v - m - s.ln()).exp());
 }
 
+// the score of a game in a score cache, which is keyed by canonical keys if `canonical`. The missing key is returned on a miss.
+// Scores are p1 winning probabilities, so they are inverted if the canonical position swapped the players.
+fn lookup_score(game: &game::Game, canonical: bool, score_map: &cache::ScoreCache) -> Result<f64, Vec<u8>> {
+    if !canonical {
+        return score_map.get(game.key_hash(), || game.key()).ok_or_else(|| game.key())
+    }
+
+    let (key, symmetry) = game.canonical_key();
+    let swapped = game.board.symmetries[symmetry].player_shift != 0;
+    match score_map.get(game.key_hash_of(&key), || key.clone()) {
+        Some(score) if swapped => Ok(1. - score),
+        Some(score) => Ok(score),
+        None => Err(key)
+    }
+}
+
 fn sample_categorical(probs: impl Iterator<Item=f64>) -> usize {
     let mut rand = get_random_float();
     for (i, p) in probs.enumerate() {
@@ -52,11 +72,17 @@ fn sample_categorical(probs: impl Iterator<Item=f64>) -> usize {
 }
 
 
+pub mod bitboard;
 pub mod board;
+pub mod cache;
+pub mod clock;
 pub mod game;
+pub mod movegen;
 pub mod alphabeta;
 pub mod greedy;
 pub mod mcts;
+pub mod render;
+pub mod tt;
 
 
 #[no_mangle]
@@ -72,132 +98,341 @@ unsafe fn write_json_buffer(value: &JsonValue) {
 unsafe fn read_json_buffer() -> serde_json::Result<JsonValue> {
     let [ptr, len, capacity] = JSON_BUFFER;
     let buffer = Vec::from_raw_parts(ptr as *mut u8, len as _, capacity as _);
-    let json = serde_json::from_slice(&buffer);
-    json
+    serde_json::from_slice(&buffer)
 }
 
 #[no_mangle]
-unsafe extern fn alloc_json_buffer(byte_length: u32) {
+unsafe extern "C" fn alloc_json_buffer(byte_length: u32) {
     let (ptr, len, capacity) = Vec::<u8>::with_capacity(byte_length as _).into_raw_parts();
     JSON_BUFFER = [ptr as _, len as _, capacity as _];
 }
 
 #[no_mangle]
-unsafe extern fn free_json_buffer() {
+unsafe extern "C" fn free_json_buffer() {
     let (ptr, len, capacity) = (JSON_BUFFER[0] as *mut u8, JSON_BUFFER[1] as _, JSON_BUFFER[2] as _);
     let _ = Vec::from_raw_parts(ptr, len, capacity);
 }
 
 #[no_mangle]
-unsafe extern fn set_random_seed(seed: u32) {
+unsafe extern "C" fn set_random_seed(seed: u32) {
     RANDOM = seed;
 }
 
+// when enabled, the keys requested by the *_poll functions are canonical keys (see Game::canonical_key),
+// so symmetric positions share the same score. Do not change it in the middle of a session.
+#[no_mangle]
+unsafe extern "C" fn set_canonical_keys(enabled: bool) {
+    CANONICAL_KEYS = enabled;
+}
+
+// when enabled, new *_poll sessions also keep the keys and panic on a hash collision. For debugging only.
+#[no_mangle]
+unsafe extern "C" fn set_collision_check(enabled: bool) {
+    CHECK_COLLISIONS = enabled;
+}
+
+// the number of entries of the transposition tables of later alphabeta searches, 0 to disable them.
+// Each entry takes 24 bytes, and each search (or timed poll session) allocates its own table.
 #[no_mangle]
-pub unsafe extern fn new_tiny_game() -> *mut game::Game {
+unsafe extern "C" fn set_transposition_table_size(size: usize) {
+    TRANSPOSITION_TABLE_SIZE = size;
+}
+
+// when disabled, later alphabeta searches try the moves in generation order, like before the move ordering was added.
+// Useful to compare the node counts of the reports.
+#[no_mangle]
+unsafe extern "C" fn set_move_ordering(enabled: bool) {
+    MOVE_ORDERING = enabled;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn new_tiny_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::TINY_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_small_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_small_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::SMALL_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_standard_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_standard_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::STANDARD_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_large_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_large_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::LARGE_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_huge_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_huge_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::HUGE_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_tiny_plus_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_tiny_plus_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::TINY_PLUS_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_small_plus_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_small_plus_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::SMALL_PLUS_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_standard_plus_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_standard_plus_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::STANDARD_PLUS_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_large_plus_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_large_plus_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::LARGE_PLUS_BOARD)))
 }
 
 #[no_mangle]
-pub unsafe extern fn new_huge_plus_game() -> *mut game::Game {
+pub unsafe extern "C" fn new_huge_plus_game() -> *mut game::Game<'static> {
     Box::leak(Box::new(game::Game::new(&board::HUGE_PLUS_BOARD)))
 }
 
+// returns null if there is no built-in board of that rank
+#[no_mangle]
+pub unsafe extern "C" fn new_game_with_rank(rank: usize, plus: bool) -> *mut game::Game<'static> {
+    new_game_with_players(rank, plus, 2)
+}
+
+// 2, 3, 4 or 6 players. Returns null if the combination is not supported
 #[no_mangle]
-pub unsafe extern fn free_game(game: *mut game::Game) {
+pub unsafe extern "C" fn new_game_with_players(rank: usize, plus: bool, n_players: usize) -> *mut game::Game<'static> {
+    match board::builtin_board(rank, plus, n_players) {
+        Some(board) => Box::leak(Box::new(game::Game::new(board))),
+        None => std::ptr::null_mut()
+    }
+}
+
+// reads a board definition (see Board::from_json) from the json buffer.
+// Returns null and writes the error message to the json buffer if it is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn load_board() -> *mut board::Board {
+    let board = read_json_buffer().map_err(|e| e.to_string()).and_then(|json| board::Board::from_json(&json).map_err(|e| e.to_string()));
+    match board {
+        Ok(board) => Box::leak(Box::new(board)),
+        Err(message) => {
+            write_json_buffer(&json!(message));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn free_board(board: *mut board::Board) {
+    let _ = Box::from_raw(board);
+}
+
+// the board must not be freed before the game
+#[no_mangle]
+pub unsafe extern "C" fn new_game_with_board(board: *const board::Board) -> *mut game::Game<'static> {
+    Box::leak(Box::new(game::Game::new(&*board)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn free_game(game: *mut game::Game<'static>) {
     let _ = Box::from_raw(game);
 }
 
 #[no_mangle]
-pub unsafe extern fn game_board_info(game: *mut game::Game) {
+pub unsafe extern "C" fn game_board_info(game: *mut game::Game<'static>) {
     let game = &*game;
     write_json_buffer(&json!({
+        "name": game.board.name,
+        "n_players": game.board.n_players,
         "n_pieces": game.board.n_pieces,
         "board_size": game.board.board_size,
+        "fingerprint": game.board.fingerprint,
     }))
 }
 
+// reads the ruleset (see Ruleset::from_json) from the json buffer and applies it to the game.
+// Returns false and writes the error message to the json buffer if it is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn game_set_ruleset(game: *mut game::Game<'static>) -> bool {
+    let game = &mut *game;
+    match read_json_buffer().map_err(|e| e.to_string()).and_then(|json| game::Ruleset::from_json(&json)) {
+        Ok(ruleset) => {
+            game.ruleset = ruleset;
+            true
+        }
+        Err(message) => {
+            write_json_buffer(&json!(message));
+            false
+        }
+    }
+}
+
+// reads a setup (see Setup::pieces_from_json) from the json buffer and restarts the game from it, keeping the ruleset.
+// Returns false and writes the error message to the json buffer if it is invalid, the game is unchanged then.
+#[no_mangle]
+pub unsafe extern "C" fn game_set_setup(game: *mut game::Game<'static>) -> bool {
+    let game = &mut *game;
+    let new_game = read_json_buffer().map_err(|e| e.to_string())
+        .and_then(|json| game::Setup::pieces_from_json(game.board, &json).map_err(|e| e.to_string()))
+        .and_then(|pieces| game::Game::with_setup(game.board, game.ruleset, pieces).map_err(|e| e.to_string()));
+    match new_game {
+        Ok(new_game) => {
+            *game = new_game;
+            true
+        }
+        Err(message) => {
+            write_json_buffer(&json!(message));
+            false
+        }
+    }
+}
+
+// null if the game started from the bases, otherwise the starting pieces and the handicap of each player, e.g.
+// {"pieces": [[0, 1, ...], [111, ...]], "handicap": [{"extra_pieces": 5, "head_start": -5}, {"extra_pieces": -2, "head_start": 3}]}
 #[no_mangle]
-pub unsafe extern fn game_is_p1_moving_next(game: *mut game::Game) -> bool {
+pub unsafe extern "C" fn game_setup(game: *mut game::Game<'static>) {
+    let game = &*game;
+    write_json_buffer(&match &game.setup {
+        None => JsonValue::Null,
+        Some(setup) => json!({
+            "pieces": setup.pieces,
+            "handicap": (0..game.board.n_players).map(|player| {
+                let handicap = setup.handicap(game.board, player);
+                json!({ "extra_pieces": handicap.extra_pieces, "head_start": handicap.head_start })
+            }).collect::<Vec<_>>(),
+        }),
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn game_ruleset(game: *mut game::Game<'static>) {
+    let game = &*game;
+    write_json_buffer(&json!({
+        "max_hop_distance": game.ruleset.max_hop_distance,
+        "max_hops": game.ruleset.max_hops,
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn game_player_to_move(game: *mut game::Game<'static>) -> usize {
+    let game = &*game;
+    game.player_to_move()
+}
+
+// the axial coordinates [q, r] of each position
+#[no_mangle]
+pub unsafe extern "C" fn game_coordinates(game: *mut game::Game<'static>) {
+    let game = &*game;
+    write_json_buffer(&json!(game.board.coordinates))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn game_position_at(game: *mut game::Game<'static>, q: i32, r: i32) -> Position {
+    let game = &*game;
+    game.board.position_at(q, r).unwrap_or(INVALID_POSITION)
+}
+
+// the geometric relation between two positions. direction and line_of_sight are null if they are not on a straight line.
+// Writes null if either position is off the board.
+#[no_mangle]
+pub unsafe extern "C" fn game_relation(game: *mut game::Game<'static>, a: Position, b: Position) {
+    let game = &*game;
+    let Some(distance) = game.board.hex_distance(a, b) else {
+        return write_json_buffer(&JsonValue::Null)
+    };
+    write_json_buffer(&json!({
+        "distance": distance,
+        "direction": game.board.direction(a, b),
+        "line_of_sight": game.board.line_of_sight(a, b),
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn game_is_p1_moving_next(game: *mut game::Game<'static>) -> bool {
     let game = &*game;
     game.is_p1_moving_next()
 }
 
 #[no_mangle]
-pub unsafe extern fn game_is_p2_moving_next(game: *mut game::Game) -> bool {
+pub unsafe extern "C" fn game_is_p2_moving_next(game: *mut game::Game<'static>) -> bool {
     let game = &*game;
     game.is_p2_moving_next()
 }
 
 #[no_mangle]
-pub unsafe extern fn game_p1_pieces(game: *mut game::Game) {
+pub unsafe extern "C" fn game_p1_pieces(game: *mut game::Game<'static>) {
     let game = &*game;
     write_json_buffer(&json!(game.p1_pieces_slice()))
 }
 
 #[no_mangle]
-pub unsafe extern fn game_p2_pieces(game: *mut game::Game) {
+pub unsafe extern "C" fn game_p2_pieces(game: *mut game::Game<'static>) {
     let game = &*game;
     write_json_buffer(&json!(game.p2_pieces_slice()))
 }
 
+// null if there is no such player
 #[no_mangle]
-pub unsafe extern fn game_get_status(game: *mut game::Game) -> u8 {
+pub unsafe extern "C" fn game_pieces(game: *mut game::Game<'static>, player: usize) {
     let game = &*game;
-    if game.expand(false).0.is_empty() {
-        match game.p1_distance().cmp(&game.p2_distance()) {
-            std::cmp::Ordering::Less => 1,
-            std::cmp::Ordering::Greater => 2,
-            std::cmp::Ordering::Equal => 3,
-        }
-    } else {
-        0
+    if player >= game.board.n_players {
+        return write_json_buffer(&JsonValue::Null)
+    }
+    write_json_buffer(&json!(game.pieces_slice(player)))
+}
+
+// 0 while the game goes on, 1 + the winner when it ends, or 3 for a two-player tie or draw by repetition.
+// With more than two players 3 is the win of the third player, so a tie is 255 there. See game_status for the reason.
+#[no_mangle]
+pub unsafe extern "C" fn game_get_status(game: *mut game::Game<'static>) -> u8 {
+    let game = &*game;
+    match game.status() {
+        game::Status::Ongoing => 0,
+        game::Status::Ended { winner: Some(winner), .. } => 1 + winner as u8,
+        game::Status::Ended { winner: None, .. } if game.board.n_players > 2 => 255,
+        game::Status::Ended { winner: None, .. } => 3,
     }
 }
 
+// {"ended": true, "winner": 0, "reason": "target_blocked"}. winner is null for a tie, reason is one of
+// "target_filled", "target_blocked", "no_moves" and "repetition", or null while the game goes on
+#[no_mangle]
+pub unsafe extern "C" fn game_status(game: *mut game::Game<'static>) {
+    let game = &*game;
+    write_json_buffer(&match game.status() {
+        game::Status::Ongoing => json!({ "ended": false, "winner": null, "reason": null }),
+        game::Status::Ended { winner, reason } => json!({
+            "ended": true,
+            "winner": winner,
+            "reason": match reason {
+                game::EndReason::TargetFilled => "target_filled",
+                game::EndReason::TargetBlocked => "target_blocked",
+                game::EndReason::NoMoves => "no_moves",
+                game::EndReason::Repetition => "repetition",
+            },
+        }),
+    })
+}
+
+// how many times the current position occurred before. The game is drawn at 2
+#[no_mangle]
+pub unsafe extern "C" fn game_repetitions(game: *mut game::Game<'static>) -> usize {
+    let game = &*game;
+    game.repetitions()
+}
+
+// players that have finished, followed by the others sorted by their distances
+#[no_mangle]
+pub unsafe extern "C" fn game_finishing_order(game: *mut game::Game<'static>) {
+    let game = &*game;
+    write_json_buffer(&json!(game.finishing_order()))
+}
+
 // a score used by model2
 #[no_mangle]
-pub unsafe extern fn game_distance_diff_score(game: *mut game::Game) -> f64 {
+pub unsafe extern "C" fn game_distance_diff_score(game: *mut game::Game<'static>) -> f64 {
     let game = &*game;
     let d1 = game.p1_distance();
     let d2 = game.p2_distance();
@@ -206,30 +441,153 @@ pub unsafe extern fn game_distance_diff_score(game: *mut game::Game) -> f64 {
     }
 
     let diff = d2 as f64 - d1 as f64;
-    return diff.signum() * (1. + diff.abs().sqrt()) / 2.
+    diff.signum() * (1. + diff.abs().sqrt()) / 2.
 }
 
+// does not check the move, see game_try_move
 #[no_mangle]
-pub unsafe extern fn game_move_to(game: *mut game::Game, from: u8, to: u8) {
+pub unsafe extern "C" fn game_move_to(game: *mut game::Game<'static>, from: u8, to: u8) {
     let game = &mut *game;
     *game = game.move_to(from, to);
 }
 
+// moves only if the move is legal. Returns 0 on success, or the error code (see MoveError::code)
+// and writes the error message to the json buffer. The game is unchanged on error.
+#[no_mangle]
+pub unsafe extern "C" fn game_try_move(game: *mut game::Game<'static>, from: u8, to: u8) -> u8 {
+    let game = &mut *game;
+    match game.try_move(from, to) {
+        Ok(next_state) => {
+            *game = next_state;
+            0
+        }
+        Err(e) => {
+            write_json_buffer(&json!(e.to_string()));
+            e.code()
+        }
+    }
+}
+
+// the position editor. Each edit returns 0 on success, or the error code (see EditError::code) and writes the error message
+// to the json buffer. The game is unchanged on error. Edits clear the history, see Game::place_piece and the others.
+#[no_mangle]
+pub unsafe extern "C" fn game_place_piece(game: *mut game::Game<'static>, player: usize, pos: u8) -> u8 {
+    let game = &mut *game;
+    edit_result(game.place_piece(player, pos))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn game_remove_piece(game: *mut game::Game<'static>, pos: u8) -> u8 {
+    let game = &mut *game;
+    edit_result(game.remove_piece(pos))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn game_move_piece(game: *mut game::Game<'static>, from: u8, to: u8) -> u8 {
+    let game = &mut *game;
+    edit_result(game.move_piece(from, to))
+}
+
 #[no_mangle]
-pub unsafe extern fn game_possible_moves_with_path(game: *mut game::Game, piece: u8) {
+pub unsafe extern "C" fn game_set_player_to_move(game: *mut game::Game<'static>, player: usize) -> u8 {
+    let game = &mut *game;
+    edit_result(game.set_player_to_move(player))
+}
+
+unsafe fn edit_result(result: Result<(), game::EditError>) -> u8 {
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_json_buffer(&json!(e.to_string()));
+            e.code()
+        }
+    }
+}
+
+// whether the position can occur in a game from the bases and whether the game is over, e.g. after editing.
+// {"reachable": false, "unreachable_reason": "player 1 has 9 pieces instead of 10", "terminal": false}
+#[no_mangle]
+pub unsafe extern "C" fn game_position_report(game: *mut game::Game<'static>) {
+    let game = &*game;
+    let reachable = game.check_reachable();
+    write_json_buffer(&json!({
+        "reachable": reachable.is_ok(),
+        "unreachable_reason": reachable.err().map(|reason| reason.to_string()),
+        "terminal": game.is_terminal(),
+    }))
+}
+
+// reads the options (see SvgOptions::from_json) from the json buffer and writes the svg as a json string
+#[no_mangle]
+pub unsafe extern "C" fn game_to_svg(game: *mut game::Game<'static>) {
+    let game = &*game;
+    let options = render::SvgOptions::from_json(&read_json_buffer().unwrap_or_default());
+    write_json_buffer(&json!(game.to_svg(&options)));
+}
+
+// reads the options (see TextOptions::from_json) from the json buffer and writes the text as a json string
+#[no_mangle]
+pub unsafe extern "C" fn game_to_text(game: *mut game::Game<'static>) {
+    let game = &*game;
+    let options = render::TextOptions::from_json(&read_json_buffer().unwrap_or_default());
+    write_json_buffer(&json!(game.to_text(&options)));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn game_possible_moves_with_path(game: *mut game::Game<'static>, piece: u8) {
     let game = &*game;
     let moves = game.possible_moves_with_path(piece);
     write_json_buffer(&json!(moves));
 }
 
+// every legal move as the list of cells it visits, e.g. [[3, 14], [5, 16, 38]]
+#[no_mangle]
+pub unsafe extern "C" fn game_action_paths(game: *mut game::Game<'static>) {
+    let game = &*game;
+    let (_, paths) = game.expand_with_paths();
+    write_json_buffer(&json!(paths))
+}
+
+// pages through the legal moves without generating all of them, e.g. on huge boards. Pass null to start and the
+// returned cursor to continue. Writes up to `limit` moves as paths (like game_action_paths) and returns null
+// with the last page. The game must not change in between, call free_move_cursor to stop early.
+// A cursor passed with another game, or after the game moved, is left alone: null is written and the cursor is returned.
 #[no_mangle]
-pub unsafe extern fn game_turn(game: *mut game::Game) -> usize {
+pub unsafe extern "C" fn game_move_page(game: *mut game::Game<'static>, limit: usize, mut cursor: *mut movegen::MovePage) -> *mut movegen::MovePage {
+    let game = &*game;
+    if cursor.is_null() {
+        cursor = Box::leak(Box::new(movegen::MovePage::new(game)));
+    }
+
+    match (*cursor).next_paths(game, limit) {
+        Some((paths, last)) => {
+            write_json_buffer(&json!(paths));
+            if last {
+                free_move_cursor(cursor);
+                return std::ptr::null_mut()
+            }
+            cursor
+        }
+        None => {
+            write_json_buffer(&JsonValue::Null);
+            cursor
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn free_move_cursor(cursor: *mut movegen::MovePage) {
+    let _ = Box::from_raw(cursor);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn game_turn(game: *mut game::Game<'static>) -> usize {
     let game = &*game;
     game.turn
 }
 
 #[no_mangle]
-pub unsafe extern fn game_expand(game: *mut game::Game) {
+pub unsafe extern "C" fn game_expand(game: *mut game::Game<'static>) {
     let game = &*game;
     let (next_states, _) = game.expand(false);
     let next_state_keys = next_states.into_iter().map(|next_state| next_state.key()).collect::<Vec<_>>();
@@ -237,32 +595,104 @@ pub unsafe extern fn game_expand(game: *mut game::Game) {
 }
 
 #[no_mangle]
-pub unsafe extern fn game_key(game: *mut game::Game) {
+pub unsafe extern "C" fn game_key(game: *mut game::Game<'static>) {
     let game = &mut *game;
     write_json_buffer(&json!(game.key()));
 }
 
+// the canonical key and the index of the symmetry that produced it
+#[no_mangle]
+pub unsafe extern "C" fn game_canonical_key(game: *mut game::Game<'static>) {
+    let game = &*game;
+    let (key, symmetry) = game.canonical_key();
+    write_json_buffer(&json!({ "key": key, "symmetry": symmetry }));
+}
+
+// the symmetries of the board. Each one maps position i to permutation[i] and player i to (i + player_shift) % n_players.
 #[no_mangle]
-pub unsafe extern fn game_load_key(game: *mut game::Game) {
+pub unsafe extern "C" fn game_symmetries(game: *mut game::Game<'static>) {
+    let game = &*game;
+    let symmetries = game.board.symmetries.iter().map(|symmetry| json!({
+        "permutation": symmetry.permutation,
+        "player_shift": symmetry.player_shift,
+    })).collect::<Vec<_>>();
+    write_json_buffer(&json!(symmetries));
+}
+
+// reads a key (see Game::key) from the json buffer. Returns false and writes the error message
+// to the json buffer if the key is malformed or for another board, the game is unchanged then.
+#[no_mangle]
+pub unsafe extern "C" fn game_load_key(game: *mut game::Game<'static>) -> bool {
     let game = &mut *game;
-    let key = read_json_buffer().unwrap();
-    *game = game::Game::from_key(game, &key.as_array().unwrap().iter().map(|x| x.as_u64().unwrap() as u8).collect::<Vec<_>>());
+    let key = read_json_buffer().map_err(|e| e.to_string()).and_then(|json| {
+        json.as_array().and_then(|bytes| bytes.iter().map(|x| x.as_u64().filter(|&x| x <= u8::MAX as u64).map(|x| x as u8)).collect::<Option<Vec<_>>>()).ok_or("the key must be an array of bytes".to_string())
+    });
+    match key.and_then(|key| game::Game::from_key(game, &key).map_err(|e| e.to_string())) {
+        Ok(next_state) => {
+            *game = next_state;
+            true
+        }
+        Err(message) => {
+            write_json_buffer(&json!(message));
+            false
+        }
+    }
+}
+
+// the agents write their move as the cells it visits, from the moving piece to its destination
+// The searches and the model scores rate positions for player 1 against player 2, so all agents but greedy only play
+// two-player games. They write null for other games, and the *_poll ones return null right away.
+unsafe fn two_players_only(game: &game::Game) -> bool {
+    if game.board.n_players != 2 {
+        write_json_buffer(&JsonValue::Null);
+        return false
+    }
+    true
 }
 
 #[no_mangle]
-pub unsafe extern fn alphabeta(game: *mut game::Game, depth: usize) {
+pub unsafe extern "C" fn alphabeta(game: *mut game::Game<'static>, depth: usize) {
     let game = &*game;
-    let (_next_state, action) = alphabeta::alphabeta(game, depth);
-    write_json_buffer(&json!([action.0, action.1]));
+    if !two_players_only(game) {
+        return
+    }
+    let (_next_state, action) = alphabeta::alphabeta(game, depth, MOVE_ORDERING, &mut tt::TranspositionTable::new(TRANSPOSITION_TABLE_SIZE));
+    write_json_buffer(&json!(game.action_path(action)));
 }
 
+// the report of a search as json. The moves of the expected line are given as paths too, each from the position the line reaches.
+fn report_json(game: &game::Game, report: &alphabeta::SearchReport) -> JsonValue {
+    let mut state = game.clone();
+    let pv = report.pv.iter().map(|&action| {
+        let path = state.action_path(action);
+        state = state.move_to(action.0, action.1);
+        path
+    }).collect::<Vec<_>>();
+    json!({ "path": game.action_path(report.action), "score": report.score, "pv": pv, "nodes": report.nodes, "depth": report.depth })
+}
+
+// like alphabeta, but writes {"path": [...], "score": 1.5, "pv": [[...], ...], "nodes": 1234, "depth": 3}.
+// The score is the heuristic from the view of player 1, pv is the expected line of play starting with path.
 #[no_mangle]
-pub unsafe extern fn alphabeta_poll(game: *mut game::Game, depth: usize, forward_only: bool, mut sess: *mut BTreeMap<Vec<u8>, f64>) -> *mut BTreeMap<Vec<u8>, f64> {
+pub unsafe extern "C" fn alphabeta_report(game: *mut game::Game<'static>, depth: usize) {
     let game = &*game;
+    if !two_players_only(game) {
+        return
+    }
+    let report = alphabeta::alphabeta_report(game, depth, MOVE_ORDERING, &mut tt::TranspositionTable::new(TRANSPOSITION_TABLE_SIZE));
+    write_json_buffer(&report_json(game, &report));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn alphabeta_poll(game: *mut game::Game<'static>, depth: usize, forward_only: bool, mut sess: *mut cache::ScoreCache) -> *mut cache::ScoreCache {
+    let game = &*game;
+    if !two_players_only(game) {
+        return std::ptr::null_mut()
+    }
     let first_call = sess.is_null();
 
     if first_call {
-        sess = Box::leak(Box::new(BTreeMap::new()));
+        sess = Box::leak(Box::new(cache::ScoreCache::new(CHECK_COLLISIONS)));
     }
     let map = &mut *sess;
 
@@ -272,13 +702,67 @@ pub unsafe extern fn alphabeta_poll(game: *mut game::Game, depth: usize, forward
             let x = x.as_array().unwrap();
             let key = x[0].as_array().unwrap().iter().map(|x| x.as_u64().unwrap() as u8).collect::<Vec<_>>();
             let value = x[1].as_f64().unwrap();
-            map.insert(key, value);
+            map.insert(game.key_hash_of(&key), key, value);
         }
     }
 
-    match alphabeta::alphabeta_poll(game, depth, forward_only, map) {
+    match alphabeta::alphabeta_poll(game, depth, forward_only, CANONICAL_KEYS, MOVE_ORDERING, map, &mut tt::TranspositionTable::new(TRANSPOSITION_TABLE_SIZE)) {
         Ok((_next_state, action)) => {
-            write_json_buffer(&json!([action.0, action.1]));
+            write_json_buffer(&json!(game.action_path(action)));
+            let _ = Box::from_raw(sess);
+            std::ptr::null_mut()
+        },
+        Err(keys) => {
+            write_json_buffer(&json!(keys));
+            sess
+        }
+    }
+}
+
+// millis and max_nodes of 0 mean no limit, max_depth is at least 1
+fn budget(millis: f64, max_nodes: usize, max_depth: usize) -> alphabeta::Budget {
+    alphabeta::Budget { millis: (millis > 0.).then_some(millis), nodes: (max_nodes > 0).then_some(max_nodes), max_depth: max_depth.max(1) }
+}
+
+// iterative deepening alphabeta (see alphabeta::alphabeta_timed). Writes the report like alphabeta_report, where depth is the last completed depth
+#[no_mangle]
+pub unsafe extern "C" fn alphabeta_timed(game: *mut game::Game<'static>, millis: f64, max_nodes: usize, max_depth: usize) {
+    let game = &*game;
+    if !two_players_only(game) {
+        return
+    }
+    let (_next_state, report) = alphabeta::alphabeta_timed(game, budget(millis, max_nodes, max_depth), MOVE_ORDERING, &mut tt::TranspositionTable::new(TRANSPOSITION_TABLE_SIZE));
+    write_json_buffer(&report_json(game, &report));
+}
+
+// the budget is read on the first call, when sess is null. The time includes the evaluation of the keys by the client.
+// The final report has the score as the win probability of player 1.
+#[no_mangle]
+pub unsafe extern "C" fn alphabeta_timed_poll(game: *mut game::Game<'static>, millis: f64, max_nodes: usize, max_depth: usize, forward_only: bool, mut sess: *mut alphabeta::TimedSession) -> *mut alphabeta::TimedSession {
+    let game = &*game;
+    if !two_players_only(game) {
+        return std::ptr::null_mut()
+    }
+    let first_call = sess.is_null();
+
+    if first_call {
+        sess = Box::leak(Box::new(alphabeta::new_timed_session(game, budget(millis, max_nodes, max_depth), MOVE_ORDERING, CHECK_COLLISIONS, TRANSPOSITION_TABLE_SIZE)));
+    }
+    let sess_ref = &mut *sess;
+
+    if !first_call {
+        let data = read_json_buffer().unwrap();
+        for x in data.as_array().unwrap().iter() {
+            let x = x.as_array().unwrap();
+            let key = x[0].as_array().unwrap().iter().map(|x| x.as_u64().unwrap() as u8).collect::<Vec<_>>();
+            let value = x[1].as_f64().unwrap();
+            sess_ref.score_map.insert(game.key_hash_of(&key), key, value);
+        }
+    }
+
+    match alphabeta::alphabeta_timed_poll(game, forward_only, CANONICAL_KEYS, sess_ref) {
+        Ok((_next_state, report)) => {
+            write_json_buffer(&report_json(game, &report));
             let _ = Box::from_raw(sess);
             std::ptr::null_mut()
         },
@@ -290,19 +774,22 @@ pub unsafe extern fn alphabeta_poll(game: *mut game::Game, depth: usize, forward
 }
 
 #[no_mangle]
-pub unsafe extern fn greedy(game: *mut game::Game, temp: f64) {
+pub unsafe extern "C" fn greedy(game: *mut game::Game<'static>, temp: f64) {
     let game = &*game;
     let (_next_state, action) = greedy::greedy(game, temp);
-    write_json_buffer(&json!([action.0, action.1]));
+    write_json_buffer(&json!(game.action_path(action)));
 }
 
 #[no_mangle]
-pub unsafe extern fn greedy_poll(game: *mut game::Game, temp: f64, forward_only: bool, mut sess: *mut BTreeMap<Vec<u8>, f64>) -> *mut BTreeMap<Vec<u8>, f64> {
+pub unsafe extern "C" fn greedy_poll(game: *mut game::Game<'static>, temp: f64, forward_only: bool, mut sess: *mut cache::ScoreCache) -> *mut cache::ScoreCache {
     let game = &*game;
+    if !two_players_only(game) {
+        return std::ptr::null_mut()
+    }
     let first_call = sess.is_null();
 
     if first_call {
-        sess = Box::leak(Box::new(BTreeMap::new()));
+        sess = Box::leak(Box::new(cache::ScoreCache::new(CHECK_COLLISIONS)));
     }
     let map = &mut *sess;
 
@@ -312,13 +799,13 @@ pub unsafe extern fn greedy_poll(game: *mut game::Game, temp: f64, forward_only:
             let x = x.as_array().unwrap();
             let key = x[0].as_array().unwrap().iter().map(|x| x.as_u64().unwrap() as u8).collect::<Vec<_>>();
             let value = x[1].as_f64().unwrap();
-            map.insert(key, value);
+            map.insert(game.key_hash_of(&key), key, value);
         }
     }
 
-    match greedy::greedy_poll(game, temp, forward_only, map) {
+    match greedy::greedy_poll(game, temp, forward_only, CANONICAL_KEYS, map) {
         Ok((_next_state, action)) => {
-            write_json_buffer(&json!([action.0, action.1]));
+            write_json_buffer(&json!(game.action_path(action)));
             let _ = Box::from_raw(sess);
             std::ptr::null_mut()
         },
@@ -330,19 +817,25 @@ pub unsafe extern fn greedy_poll(game: *mut game::Game, temp: f64, forward_only:
 }
 
 #[no_mangle]
-pub unsafe extern fn mcts(game: *mut game::Game, iterations: usize) {
+pub unsafe extern "C" fn mcts(game: *mut game::Game<'static>, iterations: usize) {
     let game = &*game;
+    if !two_players_only(game) {
+        return
+    }
     let (_next_state, action) = mcts::mcts(game, iterations);
-    write_json_buffer(&json!([action.0, action.1]));
+    write_json_buffer(&json!(game.action_path(action)));
 }
 
 #[no_mangle]
-pub unsafe extern fn mcts_poll(game: *mut game::Game, iterations: usize, forward_only: bool, mut sess: *mut (mcts::Node, BTreeMap<Vec<u8>, f64>)) -> *mut (mcts::Node, BTreeMap<Vec<u8>, f64>) {
+pub unsafe extern "C" fn mcts_poll(game: *mut game::Game<'static>, iterations: usize, forward_only: bool, mut sess: *mut (mcts::Node, cache::ScoreCache)) -> *mut (mcts::Node, cache::ScoreCache) {
     let game = &*game;
+    if !two_players_only(game) {
+        return std::ptr::null_mut()
+    }
     let first_call = sess.is_null();
 
     if first_call {
-        sess = Box::leak(Box::new(mcts::new_session(game.clone())));
+        sess = Box::leak(Box::new(mcts::new_session(CHECK_COLLISIONS)));
     }
     let (root, map) = &mut *sess;
 
@@ -352,13 +845,13 @@ pub unsafe extern fn mcts_poll(game: *mut game::Game, iterations: usize, forward
             let x = x.as_array().unwrap();
             let key = x[0].as_array().unwrap().iter().map(|x| x.as_u64().unwrap() as u8).collect::<Vec<_>>();
             let value = x[1].as_f64().unwrap();
-            map.insert(key, value);
+            map.insert(game.key_hash_of(&key), key, value);
         }
     }
 
-    match mcts::mcts_poll(game, iterations, forward_only, (root, map)) {
+    match mcts::mcts_poll(game, iterations, forward_only, CANONICAL_KEYS, (root, map)) {
         Ok((_next_state, action)) => {
-            write_json_buffer(&json!([action.0, action.1]));
+            write_json_buffer(&json!(game.action_path(action)));
             let _ = Box::from_raw(sess);
             std::ptr::null_mut()
         },
@@ -371,7 +864,7 @@ pub unsafe extern fn mcts_poll(game: *mut game::Game, iterations: usize, forward
 
 // a pure math function which is somehow tedieous to implement in js
 #[no_mangle]
-pub unsafe extern fn softmax_expectation(temp: f64, invert: bool) -> f64 {
+pub unsafe extern "C" fn softmax_expectation(temp: f64, invert: bool) -> f64 {
     let data = read_json_buffer().unwrap();
     let data = data.as_array().unwrap();
     let data = data.iter().map(|x| x.as_f64().unwrap()).collect::<Vec<_>>();
@@ -382,3 +875,78 @@ pub unsafe extern fn softmax_expectation(temp: f64, invert: bool) -> f64 {
     softmax(&mut prob, temp);
     data.iter().zip(prob.iter()).map(|(x, y)| x * y).sum::<f64>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // runs an ffi call and reads what it wrote. The json buffer is global, so the calls of parallel tests take turns.
+    fn ffi_json(call: impl FnOnce()) -> JsonValue {
+        static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _guard = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        call();
+        unsafe { read_json_buffer().unwrap() }
+    }
+
+    #[test]
+    fn status_of_a_blocked_target() {
+        // player 1 fills the last free cell of their target, the other cells are still held by player 2
+        let board = board::builtin_board(2, false, 2).unwrap();
+        let mut game = game::Game::new(board);
+        let (target, blocked) = (board.targets[0][0], board.targets[0][1..].to_vec());
+        let free = (0..board.board_size as Position).find(|&pos| !game.has_piece(pos) && !board.bases[0].contains(&pos) && !board.targets[0].contains(&pos)).unwrap();
+        game.move_piece(target, free).unwrap();
+        game.move_piece(board.bases[0][0], target).unwrap();
+        assert!(blocked.iter().all(|&pos| game.pieces_slice(1).contains(&pos)));
+
+        assert_eq!(game.status(), game::Status::Ended { winner: Some(0), reason: game::EndReason::TargetBlocked });
+        let status = ffi_json(|| unsafe { game_status(&mut game) });
+        assert_eq!(status, json!({ "ended": true, "winner": 0, "reason": "target_blocked" }));
+        assert_eq!(unsafe { game_get_status(&mut game) }, 1);
+    }
+
+    #[test]
+    fn pieces_of_missing_players_are_null() {
+        let mut game = game::Game::new(board::builtin_board(2, false, 3).unwrap());
+        for player in 0..3 {
+            assert_eq!(ffi_json(|| unsafe { game_pieces(&mut game, player) }), json!(game.pieces_slice(player)));
+        }
+        assert_eq!(ffi_json(|| unsafe { game_pieces(&mut game, 3) }), JsonValue::Null);
+        assert_eq!(ffi_json(|| unsafe { game_pieces(&mut game, usize::MAX) }), JsonValue::Null);
+    }
+
+    #[test]
+    fn setup_reports_the_handicap_of_each_player() {
+        let board = board::builtin_board(4, false, 2).unwrap();
+        let mut game = game::Game::new(board);
+        assert_eq!(ffi_json(|| unsafe { game_setup(&mut game) }), JsonValue::Null);
+
+        // player 1 gets a plus base, player 2 loses two pieces and has one moved to the middle
+        let (other, free) = (&board.bases[1], board.position_at(0, 0).unwrap());
+        let setup = json!([{"base": "plus"}, {"remove": [other[0], other[1], other[2]], "add": [free]}]);
+        let reported = ffi_json(|| unsafe {
+            write_json_buffer(&setup);
+            assert!(game_set_setup(&mut game));
+            game_setup(&mut game)
+        });
+
+        // the head start is the distance left to cover from the base minus the distance left from the setup,
+        // where the distance left is measured to the pieces packed at the tip of the target
+        let distance_left = |player: usize, pieces: &[Position]| {
+            let mut distances = board.distance_maps[player].clone();
+            distances.sort_unstable();
+            let distance: u64 = pieces.iter().map(|&pos| board.distance_maps[player][pos as usize]).sum();
+            distance as i64 - distances[..pieces.len()].iter().sum::<u64>() as i64
+        };
+        let mut pieces = [board.plus_base(0), [&other[3..], &[free]].concat()];
+        pieces.iter_mut().for_each(|pieces| pieces.sort_unstable()); // with_setup sorts the pieces
+        assert_eq!(reported["pieces"], json!(pieces));
+        for player in 0..2 {
+            let head_start = distance_left(player, &board.bases[player]) - distance_left(player, &pieces[player]);
+            let extra_pieces = pieces[player].len() as i64 - board.n_pieces as i64;
+            assert_eq!(reported["handicap"][player], json!({ "extra_pieces": extra_pieces, "head_start": head_start }));
+        }
+        assert_eq!(reported["handicap"][1]["extra_pieces"], json!(-2));
+        assert!(reported["handicap"][1]["head_start"].as_i64().unwrap() > 0);
+    }
+}
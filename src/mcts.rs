@@ -1,9 +1,7 @@
-use std::collections::BTreeMap;
-
-use crate::{game::{Game, Action}, random_shuffle, INVALID_POSITION};
+use crate::{cache::ScoreCache, game::{Game, Action, Status}, random_shuffle, lookup_score, INVALID_POSITION};
 
+// the tree only stores actions. The game of a node is reached by making the actions from the root.
 pub struct Node {
-    game: Game,
     action: Action,
     children: Vec<Node>,
     n_visits: usize,
@@ -12,29 +10,30 @@ pub struct Node {
 }
 
 impl Node {
-    fn expand(&mut self, forward_only: bool, score_map: &BTreeMap<Vec<u8>, f64>) -> Result<bool, Vec<Vec<u8>>> { // the bool indicates if the node is leaf
-        let (next_states, actions) = if forward_only {
-            self.game.expand_forward_only(true)
+    fn expand(&mut self, game: &mut Game, forward_only: bool, canonical: bool, score_map: &ScoreCache) -> Result<bool, Vec<Vec<u8>>> { // the bool indicates if the node is leaf
+        let actions = if forward_only {
+            game.actions_forward_only()
         } else {
-            self.game.expand(true)
+            game.actions()
         };
 
-        if next_states.is_empty() {
+        if actions.is_empty() {
             return Ok(true)
         }
 
-        let no_prioris: Vec<_> = next_states.iter().map(|g| g.key()).filter(|k| !score_map.contains_key(k)).collect();
+        let scores: Vec<_> = actions.iter().map(|&action| game.with_move(action, |g| if g.repetitions() > 0 { Ok(0.5) } else { lookup_score(g, canonical, score_map) })).collect();
+        let no_prioris: Vec<_> = scores.iter().filter_map(|score| score.clone().err()).collect();
         if !no_prioris.is_empty() {
             return Err(no_prioris)
         }
 
-        let mut zipped: Vec<_> = next_states.into_iter().zip(actions.into_iter()).collect();
+        let mut zipped: Vec<_> = actions.into_iter().zip(scores).collect();
         random_shuffle(&mut zipped);
 
-        self.children = zipped.into_iter().map(|(game, action)| {
-            let score = score_map[&game.key()];
+        self.children = zipped.into_iter().map(|(action, score)| {
+            let score = score.unwrap();
             Node {
-                game, action,
+                action,
                 children: Vec::new(),
                 n_visits: 0,
                 priori: score,
@@ -45,9 +44,9 @@ impl Node {
         Ok(false)
     }
 
-    fn select(&mut self) -> &mut Node {
+    fn select(&mut self, game: &Game) -> &mut Node {
         self.children.iter_mut().max_by_key(|child| {
-            let (q, p) = if self.game.is_p1_moving_next() {
+            let (q, p) = if game.is_p1_moving_next() {
                 (child.value, child.priori)
             } else {
                 (1. - child.value, 1. - child.priori)
@@ -58,15 +57,17 @@ impl Node {
         }).unwrap()
     }
 
-    fn playout(&mut self, forward_only: bool, score_map: &BTreeMap<Vec<u8>, f64>) -> Result<f64, Vec<Vec<u8>>> {
+    // `game` is the game of this node. It is restored before returning.
+    fn playout(&mut self, game: &mut Game, forward_only: bool, canonical: bool, score_map: &ScoreCache) -> Result<f64, Vec<Vec<u8>>> {
         if self.children.is_empty() {
             self.n_visits += 1;
 
-            let leaf_value = if self.expand(forward_only, score_map)? { // is leaf
-                match self.game.p1_distance().cmp(&self.game.p2_distance()) {
-                    std::cmp::Ordering::Less => 1.,
-                    std::cmp::Ordering::Greater => 0.,
-                    std::cmp::Ordering::Equal => 0.,
+            let leaf_value = if game.repetitions() > 0 { // a repeated position is a draw
+                0.5
+            } else if self.expand(game, forward_only, canonical, score_map)? { // is leaf
+                match game.status() {
+                    Status::Ended { winner: Some(0), .. } => 1.,
+                    _ => 0.,
                 }
             } else {
                 // we have several options here
@@ -75,10 +76,10 @@ impl Node {
                 // 3. play to end with heuristic
                 // 4. estimate the value with policy
                 // 5. estimate the vlaue with heuristic
-                match self.game.p1_distance().cmp(&self.game.p2_distance()) {
+                match game.p1_distance().cmp(&game.p2_distance()) {
                     std::cmp::Ordering::Less => 1.,
                     std::cmp::Ordering::Greater => 0.,
-                    std::cmp::Ordering::Equal => if self.game.is_p1_moving_next() {
+                    std::cmp::Ordering::Equal => if game.is_p1_moving_next() {
                         1.
                     } else {
                         0.
@@ -90,7 +91,8 @@ impl Node {
             return Ok(leaf_value)
         }
 
-        let leaf_value = self.select().playout(forward_only, score_map)?;
+        let child = self.select(game);
+        let leaf_value = game.with_move(child.action, |next_state| child.playout(next_state, forward_only, canonical, score_map))?;
 
         self.n_visits += 1;
         self.value += (leaf_value - self.value) / self.n_visits as f64;
@@ -98,31 +100,33 @@ impl Node {
     }
 }
 
-pub fn new_session(game: Game) -> (Node, BTreeMap<Vec<u8>, f64>) {
+pub fn new_session(check_collisions: bool) -> (Node, ScoreCache) {
     let action = Action(INVALID_POSITION, INVALID_POSITION);
-    (Node { game, action, children: Vec::new(), n_visits: 0, priori: 0.5, value: 0.5 }, BTreeMap::new())
+    (Node { action, children: Vec::new(), n_visits: 0, priori: 0.5, value: 0.5 }, ScoreCache::new(check_collisions))
 }
 
-pub fn mcts_poll(_game: &Game, itertions: usize, forward_only: bool, sess: (&mut Node, &BTreeMap<Vec<u8>, f64>)) -> Result<(Game, Action), Vec<Vec<u8>>> {
+// `game` must be the same in every poll of a session
+pub fn mcts_poll<'a>(game: &Game<'a>, itertions: usize, forward_only: bool, canonical: bool, sess: (&mut Node, &ScoreCache)) -> Result<(Game<'a>, Action), Vec<Vec<u8>>> {
     let (root, score_map) = sess;
+    let mut state = game.clone();
 
     while root.n_visits < itertions {
-        root.playout(forward_only, score_map)?;
+        root.playout(&mut state, forward_only, canonical, score_map)?;
     }
 
-    Ok(root.children.iter().max_by_key(|child| child.n_visits).map(|child| (child.game.clone(), child.action.clone())).unwrap())
+    Ok(root.children.iter().max_by_key(|child| child.n_visits).map(|child| (game.move_to(child.action.0, child.action.1), child.action)).unwrap())
 }
 
-pub fn mcts(game: &Game, itertions: usize) -> (Game, Action) {
-    let (mut root, mut score_map) = new_session(game.clone());
+pub fn mcts<'a>(game: &Game<'a>, itertions: usize) -> (Game<'a>, Action) {
+    let (mut root, mut score_map) = new_session(false);
 
     loop {
-        match mcts_poll(game, itertions, false, (&mut root, &score_map)) {
+        match mcts_poll(game, itertions, false, false, (&mut root, &score_map)) {
             Ok((game, action)) => return (game, action),
             Err(no_prioris) => {
                 for key in no_prioris {
                     let baseline = 2. * game.board.n_pieces as f64;
-                    let heuristic = Game::from_key(&game, &key).heuristic();
+                    let heuristic = Game::from_key(game, &key).unwrap().heuristic();
                     let value = if heuristic >= baseline {
                         1.0
                     } else if heuristic <= -baseline {
@@ -131,7 +135,7 @@ pub fn mcts(game: &Game, itertions: usize) -> (Game, Action) {
                         0.5 + heuristic / (2. * baseline)
                     };
 
-                    score_map.insert(key, value);
+                    score_map.insert(game.key_hash_of(&key), key, value);
                 }
             }
         }
@@ -0,0 +1,186 @@
+use crate::{INVALID_POSITION, Position, game::{Action, Game, path_to}};
+
+// the order in which the moves are generated. Both orders are lazy: the hops of a piece are searched when its turn comes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MoveOrder {
+    #[default]
+    Natural, // pieces and destinations by position, the same order as Game::actions
+    ForwardFirst, // the pieces farthest from the target first, each with its most advancing destinations first
+}
+
+// the state of a move generation, which does not borrow the game. The game can be changed between calls
+// as long as it is back at the same position, e.g. when each move is tried with Game::with_move.
+pub struct MoveCursor {
+    order: MoveOrder,
+    pieces: Vec<Position>, // the pieces to move, in the generation order
+    parents: Vec<Position>, // possible_moves_with_path of the current piece
+    destinations: Vec<Position>, // the sorted destinations of the current piece, only for ForwardFirst
+    next_piece: usize, // index into pieces, after the current piece
+    next_destination: usize, // index into destinations, or the position to continue the scan of parents from
+}
+
+impl MoveCursor {
+    // nothing is generated if the game has ended
+    pub fn new(game: &Game, order: MoveOrder) -> MoveCursor {
+        let player = game.player_to_move();
+        let mut pieces = if game.is_ended() { vec![] } else { game.pieces_slice(player).to_vec() };
+        if order == MoveOrder::ForwardFirst {
+            let distance_map = &game.board.distance_maps[player];
+            pieces.sort_by_key(|&piece| std::cmp::Reverse(distance_map[piece as usize])); // stable, so ties stay by position
+        }
+        MoveCursor { order, pieces, parents: vec![], destinations: vec![], next_piece: 0, next_destination: 0 }
+    }
+
+    pub fn next_action(&mut self, game: &Game) -> Option<Action> {
+        loop {
+            if let Some(dest) = self.next_destination() {
+                return Some(Action(self.pieces[self.next_piece - 1], dest))
+            }
+
+            let &piece = self.pieces.get(self.next_piece)?;
+            self.next_piece += 1;
+            self.parents = game.possible_moves_with_path(piece);
+            self.next_destination = 0;
+            if self.order == MoveOrder::ForwardFirst {
+                let distance_map = &game.board.distance_maps[game.player_to_move()];
+                self.destinations = (0..self.parents.len()).filter(|&dest| self.parents[dest] != INVALID_POSITION && dest != piece as usize).map(|dest| dest as Position).collect();
+                self.destinations.sort_by_key(|&dest| distance_map[dest as usize]);
+            }
+        }
+    }
+
+    // the next destination of the current piece. The natural order scans the parents directly.
+    fn next_destination(&mut self) -> Option<Position> {
+        let piece = *self.pieces.get(self.next_piece.checked_sub(1)?)?;
+        match self.order {
+            MoveOrder::Natural => {
+                let dest = (self.next_destination..self.parents.len()).find(|&dest| self.parents[dest] != INVALID_POSITION && dest != piece as usize)?;
+                self.next_destination = dest + 1;
+                Some(dest as Position)
+            }
+            MoveOrder::ForwardFirst => {
+                let &dest = self.destinations.get(self.next_destination)?;
+                self.next_destination += 1;
+                Some(dest)
+            }
+        }
+    }
+
+    // like next_action, but the move is given as the cells it visits (see Game::action_path)
+    pub fn next_path(&mut self, game: &Game) -> Option<Vec<Position>> {
+        let Action(from, to) = self.next_action(game)?;
+        Some(path_to(&self.parents, from, to))
+    }
+}
+
+// a MoveCursor that is kept by a client between calls, so it remembers which game and position it was started for
+pub struct MovePage {
+    game: *const Game<'static>,
+    position_hash: u64,
+    cursor: MoveCursor,
+}
+
+impl MovePage {
+    pub fn new(game: &Game<'static>) -> MovePage {
+        MovePage { game, position_hash: game.position_hash(), cursor: MoveCursor::new(game, MoveOrder::Natural) }
+    }
+
+    // up to `limit` more moves as paths, and whether they are the last ones. None if `game` is not the game the paging
+    // started with, or it has moved on since.
+    pub fn next_paths(&mut self, game: &Game<'static>, limit: usize) -> Option<(Vec<Vec<Position>>, bool)> {
+        if !std::ptr::eq(self.game, game) || self.position_hash != game.position_hash() {
+            return None
+        }
+
+        let mut paths = vec![];
+        while paths.len() < limit {
+            match self.cursor.next_path(game) {
+                Some(path) => paths.push(path),
+                None => return Some((paths, true)),
+            }
+        }
+        Some((paths, false))
+    }
+}
+
+// the legal moves of the player to move, generated one at a time. See MoveCursor to change the game in between.
+pub struct MoveGen<'g, 'a> {
+    game: &'g Game<'a>,
+    cursor: MoveCursor,
+}
+
+impl<'g, 'a> MoveGen<'g, 'a> {
+    pub fn new(game: &'g Game<'a>, order: MoveOrder) -> MoveGen<'g, 'a> {
+        MoveGen { game, cursor: MoveCursor::new(game, order) }
+    }
+}
+
+impl Iterator for MoveGen<'_, '_> {
+    type Item = Action;
+
+    fn next(&mut self) -> Option<Action> {
+        self.cursor.next_action(self.game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::builtin_board, game::raced_positions};
+
+    #[test]
+    fn every_order_generates_the_moves_of_possible_moves_with_path() {
+        let board = builtin_board(4, false, 2).unwrap();
+        for game in raced_positions(board, 40) {
+            let player = game.player_to_move();
+            let mut expected: Vec<_> = game.pieces_slice(player).iter().flat_map(|&piece| {
+                let parents = game.possible_moves_with_path(piece);
+                (0..parents.len()).filter(move |&dest| parents[dest] != INVALID_POSITION && dest != piece as usize).map(move |dest| Action(piece, dest as Position))
+            }).collect();
+            if game.is_ended() {
+                expected.clear();
+            }
+
+            for order in [None, Some(MoveOrder::Natural), Some(MoveOrder::ForwardFirst)] {
+                let mut actions: Vec<_> = game.move_gen(order).collect();
+                if order != Some(MoveOrder::ForwardFirst) {
+                    assert_eq!(actions, game.actions());
+                }
+                actions.sort_unstable_by_key(|&Action(from, to)| (from, to));
+                expected.sort_unstable_by_key(|&Action(from, to)| (from, to));
+                assert_eq!(actions, expected, "{order:?}");
+            }
+
+            // pieces from the farthest, then their destinations from the most advancing
+            let distance_map = &board.distance_maps[player];
+            let forward: Vec<_> = game.move_gen(Some(MoveOrder::ForwardFirst)).map(|Action(from, to)| (distance_map[from as usize], from, distance_map[to as usize])).collect();
+            assert!(forward.windows(2).all(|w| w[0].0 > w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1) || (w[0].1 == w[1].1 && w[0].2 <= w[1].2)));
+        }
+    }
+
+    #[test]
+    fn pages_cover_the_moves_of_their_own_game_only() {
+        let board = builtin_board(4, false, 2).unwrap();
+        let mut game = Game::new(board);
+        let (_, expected) = game.expand_with_paths();
+
+        let mut page = MovePage::new(&game);
+        let mut paths = vec![];
+        loop {
+            let (next, last) = page.next_paths(&game, 7).unwrap();
+            assert!(next.len() <= 7);
+            paths.extend(next);
+            if last {
+                break
+            }
+        }
+        assert_eq!(paths, expected);
+
+        let mut page = MovePage::new(&game);
+        let other = game.clone();
+        assert!(page.next_paths(&other, 7).is_none());
+        let Action(from, to) = game.actions()[0];
+        game.make_move(from, to);
+        assert!(page.next_paths(&game, 7).is_none());
+    }
+}
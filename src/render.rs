@@ -0,0 +1,234 @@
+use std::fmt::Write;
+
+use serde_json::Value as JsonValue;
+
+use crate::{INVALID_POSITION, Position, game::Game};
+
+const PLAYER_COLORS: [&str; 6] = ["#e53935", "#1e88e5", "#43a047", "#fdd835", "#8e24aa", "#fb8c00"];
+const SCALE: f64 = 20.; // distance between neibouring cells
+const ASCII_SYMBOLS: [char; 6] = ['X', 'O', 'A', 'B', 'C', 'D'];
+const UNICODE_SYMBOLS: [char; 6] = ['●', '○', '▲', '△', '■', '□'];
+
+// None for anything but a position, values that do not fit are not wrapped. Positions off the board are skipped when rendering.
+fn json_position(json: &JsonValue) -> Option<Position> {
+    json.as_u64().and_then(|p| Position::try_from(p).ok()).filter(|&p| p != INVALID_POSITION)
+}
+
+#[derive(Default)]
+pub struct SvgOptions {
+    pub labels: bool, // write the position number in each cell
+    pub highlight: Option<Position>, // draw the hop paths to every destination of this piece
+    pub last_move: Vec<Position>, // the cells visited by the last move, drawn as arrows
+}
+
+impl SvgOptions {
+    // {"labels": true, "highlight": 7, "last_move": [3, 12, 23]}, all fields are optional
+    pub fn from_json(json: &JsonValue) -> SvgOptions {
+        SvgOptions {
+            labels: json["labels"].as_bool().unwrap_or(false),
+            highlight: json_position(&json["highlight"]),
+            last_move: json["last_move"].as_array().map(|path| path.iter().map(|p| json_position(p).unwrap_or(INVALID_POSITION)).collect()).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TextOptions {
+    pub unicode: bool, // use unicode symbols for the pieces instead of letters
+    pub highlight: Option<Position>, // mark every destination of this piece with '*'
+}
+
+impl TextOptions {
+    // {"unicode": true, "highlight": 7}, all fields are optional
+    pub fn from_json(json: &JsonValue) -> TextOptions {
+        TextOptions {
+            unicode: json["unicode"].as_bool().unwrap_or(false),
+            highlight: json_position(&json["highlight"]),
+        }
+    }
+}
+
+impl Game<'_> {
+    // cartesian coordinates in the svg, y goes down
+    fn svg_point(&self, pos: Position) -> (f64, f64) {
+        let (q, r) = self.board.coordinates[pos as usize];
+        (SCALE * (q as f64 + r as f64 / 2.), -SCALE * r as f64 * 3f64.sqrt() / 2.)
+    }
+
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let points: Vec<_> = (0..self.board.board_size).map(|pos| self.svg_point(pos as _)).collect();
+        let min_x = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min) - SCALE;
+        let max_x = points.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max) + SCALE;
+        let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min) - SCALE;
+        let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max) + SCALE;
+
+        let mut svg = String::new();
+        let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(svg, r#"<svg viewBox="{:.2} {:.2} {:.2} {:.2}" xmlns="http://www.w3.org/2000/svg" version="1.1">"#, min_x, min_y, max_x - min_x, max_y - min_y);
+        let _ = writeln!(svg, r#"<style> .t {{ font: italic 6px sans-serif; }} </style>"#);
+        let _ = writeln!(svg, r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="8" refY="5" markerWidth="4" markerHeight="4" orient="auto"><path d="M 0 0 L 10 5 L 0 10 z"/></marker></defs>"#);
+
+        for &(x, y) in &points {
+            let _ = writeln!(svg, r#"<circle cx="{x:.2}" cy="{y:.2}" r="8" stroke="black" fill="transparent"/>"#);
+        }
+
+        for player in 0..self.board.n_players {
+            for &piece in self.pieces_slice(player) {
+                let (x, y) = points[piece as usize];
+                let _ = writeln!(svg, r#"<circle cx="{x:.2}" cy="{y:.2}" r="7" stroke="black" fill="{}"/>"#, PLAYER_COLORS[player % PLAYER_COLORS.len()]);
+            }
+        }
+
+        // every destination is reached from its parent in the path array, so drawing all parent links draws the whole hop tree
+        if let Some(piece) = options.highlight.filter(|&piece| (piece as usize) < self.board.board_size) {
+            let paths = self.possible_moves_with_path(piece);
+            for (dest, &parent) in paths.iter().enumerate() {
+                if parent == INVALID_POSITION || dest == piece as usize {
+                    continue
+                }
+                let ((x1, y1), (x2, y2)) = (points[parent as usize], points[dest]);
+                let _ = writeln!(svg, r#"<line x1="{x1:.2}" y1="{y1:.2}" x2="{x2:.2}" y2="{y2:.2}" stroke="gray" stroke-dasharray="2 2"/>"#);
+                let _ = writeln!(svg, r#"<circle cx="{x2:.2}" cy="{y2:.2}" r="3" fill="gray"/>"#);
+            }
+        }
+
+        for hop in options.last_move.windows(2).filter(|hop| hop.iter().all(|&p| (p as usize) < self.board.board_size)) {
+            let ((x1, y1), (x2, y2)) = (points[hop[0] as usize], points[hop[1] as usize]);
+            let _ = writeln!(svg, r#"<line x1="{x1:.2}" y1="{y1:.2}" x2="{x2:.2}" y2="{y2:.2}" stroke="black" stroke-width="1.5" marker-end="url(#arrow)"/>"#);
+        }
+
+        if options.labels {
+            for (pos, &(x, y)) in points.iter().enumerate() {
+                let _ = writeln!(svg, r#"<text x="{x:.2}" y="{:.2}" class="t" alignment-baseline="middle" text-anchor="middle">{pos}</text>"#, y + 0.5);
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // the board as a staggered hex grid, one line per row with p1 at the bottom. Empty cells are '.'
+    pub fn to_text(&self, options: &TextOptions) -> String {
+        let symbols = if options.unicode { UNICODE_SYMBOLS } else { ASCII_SYMBOLS };
+        let mut cells = vec!['.'; self.board.board_size];
+
+        if let Some(piece) = options.highlight.filter(|&piece| (piece as usize) < self.board.board_size) {
+            for (dest, &parent) in self.possible_moves_with_path(piece).iter().enumerate() {
+                if parent != INVALID_POSITION && dest != piece as usize {
+                    cells[dest] = '*';
+                }
+            }
+        }
+
+        for player in 0..self.board.n_players {
+            for &piece in self.pieces_slice(player) {
+                cells[piece as usize] = symbols[player % symbols.len()];
+            }
+        }
+
+        // cells in the same row share r, and neibours in a row are two columns apart
+        let columns: Vec<_> = self.board.coordinates.iter().map(|&(q, r)| 2 * q + r).collect();
+        let min_column = columns.iter().copied().min().unwrap_or(0);
+        let mut rows: Vec<_> = self.board.coordinates.iter().map(|&(_, r)| r).collect();
+        rows.sort_unstable();
+        rows.dedup();
+
+        let mut text = String::new();
+        for &row in rows.iter().rev() {
+            let mut line = String::new();
+            let mut width = 0;
+            let mut row_positions: Vec<_> = (0..self.board.board_size).filter(|&pos| self.board.coordinates[pos].1 == row).collect();
+            row_positions.sort_unstable_by_key(|&pos| columns[pos]); // custom boards may number the cells of a row in any order
+            for pos in row_positions {
+                let column = (columns[pos] - min_column) as usize;
+                while width < column {
+                    line.push(' ');
+                    width += 1;
+                }
+                line.push(cells[pos]);
+                width += 1;
+            }
+            text.push_str(&line);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl std::fmt::Display for Game<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_text(&TextOptions::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::{Board, builtin_board}, game::Action};
+
+    #[test]
+    fn options_ignore_positions_that_do_not_fit() {
+        let options = SvgOptions::from_json(&serde_json::json!({"highlight": 300, "last_move": [3, 300, 12]}));
+        assert_eq!((options.highlight, options.last_move), (None, vec![3, INVALID_POSITION, 12]));
+        assert_eq!(TextOptions::from_json(&serde_json::json!({"highlight": 255})).highlight, None);
+        assert_eq!(TextOptions::from_json(&serde_json::json!({"highlight": 7})).highlight, Some(7));
+    }
+
+    #[test]
+    fn svg_draws_pieces_highlights_arrows_and_labels() {
+        let board = builtin_board(3, false, 3).unwrap();
+        let game = Game::new(board);
+        let action = game.actions()[0];
+        let n_destinations = game.actions().iter().filter(|&&Action(from, _)| from == action.0).count();
+        let count = |svg: &str, pattern: &str| svg.matches(pattern).count();
+
+        let svg = game.to_svg(&SvgOptions::default());
+        for color in &PLAYER_COLORS[..board.n_players] {
+            assert_eq!(count(&svg, &format!(r#"r="7" stroke="black" fill="{color}""#)), board.n_pieces);
+        }
+        assert_eq!(count(&svg, r#"r="8""#), board.board_size);
+        assert_eq!(count(&svg, r#"fill="gray""#), 0);
+        assert_eq!(count(&svg, "marker-end"), 0);
+        assert_eq!(count(&svg, "<text"), 0);
+
+        let path = game.action_path(action);
+        let svg = game.to_svg(&SvgOptions { labels: true, highlight: Some(action.0), last_move: path.clone() });
+        assert_eq!(count(&svg, r#"r="3" fill="gray""#), n_destinations);
+        assert_eq!(count(&svg, r#"stroke-dasharray="2 2""#), n_destinations);
+        assert_eq!(count(&svg, r#"marker-end="url(#arrow)""#), path.len() - 1);
+        assert_eq!(count(&svg, "<text"), board.board_size);
+        assert!(svg.contains(&format!(">{}</text>", board.board_size - 1)));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn text_of_a_builtin_board() {
+        let game = Game::new(builtin_board(2, false, 2).unwrap());
+        let expected = [
+            "      O",
+            "     O O",
+            ". . . . . . .",
+            " . . . . . .",
+            "  . . . . .",
+            " . . . . . .",
+            ". . . . . . .",
+            "     X X",
+            "      X",
+        ];
+        assert_eq!(game.to_text(&TextOptions::default()), expected.map(|line| line.to_string() + "\n").concat());
+        assert_eq!(game.to_string(), game.to_text(&TextOptions::default()));
+    }
+
+    #[test]
+    fn text_puts_cells_in_column_order() {
+        // position 1 is left of position 0
+        let json = serde_json::json!({
+            "name": "pair",
+            "adjacency": [[null, null, null, null, null, 1], [null, null, 0, null, null, null]],
+            "coordinates": [[0, 0], [-1, 0]],
+            "bases": [[0], [1]],
+        });
+        let board = Board::from_json(&json).unwrap();
+        assert_eq!(Game::new(&board).to_text(&TextOptions::default()), "O X\n");
+    }
+}
@@ -0,0 +1,84 @@
+use crate::game::Action;
+
+// how the stored value relates to the true value of the position
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound {
+    Exact,
+    Lower, // the search failed high, the value is at least this
+    Upper, // the search failed low, the value is at most this
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    pub hash: u64, // the position hash (see Game::position_hash), to tell apart positions sharing the slot
+    pub depth: u32, // the remaining depth of the search that produced the value
+    pub bound: Bound,
+    pub value: f64,
+    pub best: Option<Action>, // the best or refuting move, searched first next time
+}
+
+impl Entry {
+    // the value if it settles the search of this depth and window
+    pub fn cutoff(&self, depth: usize, alpha: f64, beta: f64) -> Option<f64> {
+        if (self.depth as usize) < depth {
+            return None
+        }
+        match self.bound {
+            Bound::Exact => Some(self.value),
+            Bound::Lower if self.value >= beta => Some(self.value),
+            Bound::Upper if self.value <= alpha => Some(self.value),
+            _ => None
+        }
+    }
+}
+
+// a fixed-size table of search results, indexed by the position hash. A new position takes over the slot of another one,
+// while a shallower result never replaces a deeper one of the same position.
+// Stored values ignore the path, so a draw by repetition found through one path may be reused through another.
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    // a table of size 0 stores nothing
+    pub fn new(size: usize) -> TranspositionTable {
+        TranspositionTable { entries: vec![None; size] }
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<Entry> {
+        if self.entries.is_empty() {
+            return None
+        }
+        self.entries[hash as usize % self.entries.len()].filter(|entry| entry.hash == hash)
+    }
+
+    pub fn store(&mut self, entry: Entry) {
+        if self.entries.is_empty() {
+            return
+        }
+        let len = self.entries.len();
+        let slot = &mut self.entries[entry.hash as usize % len];
+        if slot.is_none_or(|old| old.hash != entry.hash || old.depth <= entry.depth) {
+            *slot = Some(entry);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// the bound of a value returned by a search with the window (alpha, beta)
+pub fn bound_of(value: f64, alpha: f64, beta: f64) -> Bound {
+    if value <= alpha {
+        Bound::Upper
+    } else if value >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    }
+}